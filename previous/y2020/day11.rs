@@ -1,3 +1,7 @@
+use crate::automaton::{self, Automaton, Dense, Neighborhood};
+use crate::geometry::Point2;
+use crate::grid::Grid;
+use crate::visualizer::{self, Cell, Frame, Rgb, Visualizer};
 use nom::{
     branch::alt,
     bytes::complete::tag,
@@ -6,7 +10,7 @@ use nom::{
     IResult,
 };
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 enum Position {
     Floor,
     EmptySeat,
@@ -14,7 +18,15 @@ enum Position {
 }
 
 impl Position {
-    fn apply_rule(&self, adjacent: &[&Position]) -> Position {
+    fn cell(&self) -> Cell {
+        match self {
+            Position::Floor => Cell::new('.', Rgb(60, 60, 60)),
+            Position::EmptySeat => Cell::new('L', Rgb(80, 160, 80)),
+            Position::OccupiedSeat => Cell::new('#', Rgb(220, 80, 60)),
+        }
+    }
+
+    fn apply_rule(&self, adjacent: &[&Position], tolerance: usize) -> Position {
         match self {
             Position::EmptySeat => {
                 if adjacent.iter().any(|&v| v == &Position::OccupiedSeat) {
@@ -28,7 +40,7 @@ impl Position {
                     .iter()
                     .filter(|&&v| v == &Position::OccupiedSeat)
                     .count()
-                    >= 4
+                    >= tolerance
                 {
                     Position::EmptySeat
                 } else {
@@ -40,29 +52,34 @@ impl Position {
     }
 }
 
-fn adjacent<'a>((x, y): (usize, usize), grid: &'a Vec<Vec<Position>>) -> Vec<&'a Position> {
-    let mut results = vec![];
+#[derive(Debug, Clone, Copy)]
+enum Seating {
+    Adjacent,
+    LineOfSight,
+}
 
-    if x > 0 {
-        results.push(grid.get(y + 1).and_then(|v| v.get(x - 1)));
-        results.push(grid.get(y).and_then(|v| v.get(x - 1)));
+impl Neighborhood<Position> for Seating {
+    fn neighbors<'a>(
+        &self,
+        grid: &'a Grid<Position>,
+        point: Point2<usize>,
+        into: &mut Vec<&'a Position>,
+    ) {
+        match self {
+            Seating::Adjacent => automaton::Adjacent.neighbors(grid, point, into),
+            Seating::LineOfSight => automaton::LineOfSight(|p: &Position| p != &Position::Floor)
+                .neighbors(grid, point, into),
+        }
     }
+}
 
-    if y > 0 {
-        if x > 0 {
-            results.push(grid.get(y - 1).and_then(|v| v.get(x - 1)));
+impl Seating {
+    fn tolerance(&self) -> usize {
+        match self {
+            Seating::Adjacent => 4,
+            Seating::LineOfSight => 5,
         }
-
-        results.push(grid.get(y - 1).and_then(|v| v.get(x)));
-        results.push(grid.get(y - 1).and_then(|v| v.get(x + 1)));
     }
-
-    results.extend(vec![
-        grid.get(y + 1).and_then(|v| v.get(x)),
-        grid.get(y + 1).and_then(|v| v.get(x + 1)),
-        grid.get(y).and_then(|v| v.get(x + 1)),
-    ]);
-    results.into_iter().filter_map(|v| v).collect()
 }
 
 fn parse_grid(input: &str) -> IResult<&str, Vec<Vec<Position>>> {
@@ -85,47 +102,92 @@ fn parse_occupied(input: &str) -> IResult<&str, Position> {
     map(tag("#"), |_| Position::OccupiedSeat)(input)
 }
 
-fn build_new_grid(old: &Vec<Vec<Position>>) -> Option<Vec<Vec<Position>>> {
-    let mut grid = vec![];
+pub fn solve() {
+    let input = include_str!("input-day11");
 
-    for (y, row) in old.iter().enumerate() {
-        let mut new_row: Vec<Position> = vec![];
+    println!("Solution: {:?}", run(input));
+}
 
-        for (x, position) in row.iter().enumerate() {
-            new_row.push(position.apply_rule(&adjacent((x, y), old)));
-        }
+fn run(input: &str) -> (usize, usize) {
+    let grid = Grid::new(parse_grid(input).unwrap().1);
 
-        grid.push(new_row);
-    }
+    (
+        occupied(&settle(grid.clone(), Seating::Adjacent, &mut ())),
+        occupied(&settle(grid, Seating::LineOfSight, &mut ())),
+    )
+}
 
-    if old == &grid {
-        None
-    } else {
-        Some(grid)
-    }
+fn occupied(grid: &Grid<Position>) -> usize {
+    grid.iter()
+        .filter(|(_, &v)| v == Position::OccupiedSeat)
+        .count()
 }
 
-pub fn solve() {
+pub fn animate(visualizer: &mut impl Visualizer) -> std::io::Result<()> {
     let input = include_str!("input-day11");
-    let grid = parse_grid(&input).unwrap().1;
+    let grid = Grid::new(parse_grid(input).unwrap().1);
 
-    let mut last_grid = Some(grid);
+    settle(grid, Seating::Adjacent, visualizer);
 
-    loop {
-        if let Some(last_grid_) = last_grid.as_ref().and_then(|v| build_new_grid(v)) {
-            last_grid = Some(last_grid_);
-        } else {
-            break;
-        }
-    }
+    visualizer.finish()
+}
+
+fn seats(
+    grid: Grid<Position>,
+    seating: Seating,
+) -> Dense<Position, Seating, impl Fn(&Position, &[&Position]) -> Position + Sync + Clone> {
+    let tolerance = seating.tolerance();
+
+    Dense::new(
+        grid,
+        seating,
+        move |position: &Position, neighbors: &[&Position]| {
+            position.apply_rule(neighbors, tolerance)
+        },
+    )
+}
 
-    println!(
-        "{:?}",
-        last_grid
-            .unwrap()
-            .iter()
-            .flatten()
-            .filter(|&v| v == &Position::OccupiedSeat)
-            .count()
-    );
+fn settle(
+    grid: Grid<Position>,
+    seating: Seating,
+    visualizer: &mut impl Visualizer,
+) -> Grid<Position> {
+    let mut seats = seats(grid, seating);
+
+    visualizer::record(visualizer, || {
+        Frame::from_rows(seats.grid().rows(), Position::cell)
+    });
+    seats.settle_with(|seats| {
+        visualizer::record(visualizer, || {
+            Frame::from_rows(seats.grid().rows(), Position::cell)
+        })
+    });
+
+    seats.into_grid()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settles_with_both_seating_rules() {
+        let input = "L.LL.LL.LL
+LLLLLLL.LL
+L.L.L..L..
+LLLL.LL.LL
+L.LL.LL.LL
+L.LLLLL.LL
+..L.L.....
+LLLLLLLLLL
+L.LLLLLL.L
+L.LLLLL.LL";
+
+        assert_eq!(run(input), (37, 26));
+
+        let grid = Grid::new(parse_grid(input).unwrap().1);
+        let seats = seats(grid, Seating::Adjacent);
+        assert_eq!(occupied(&seats.after(1_000_000_000).into_grid()), 37);
+        assert_eq!(occupied(&seats.after(1).into_grid()), 71);
+    }
 }
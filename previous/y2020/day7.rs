@@ -1,3 +1,4 @@
+use crate::dag::{Dag, DagError};
 use crate::parser::*;
 use nom::{
     branch::alt,
@@ -7,7 +8,6 @@ use nom::{
     sequence::{separated_pair, terminated},
     IResult,
 };
-use std::collections::HashMap;
 
 #[derive(Debug, PartialEq)]
 enum ColorRule<'a> {
@@ -48,38 +48,83 @@ fn parse_contains(input: &str) -> IResult<&str, ColorRule> {
 pub fn solve() {
     let input = include_str!("input-day7");
 
-    let (_, all) = separated_list1(tag("\n"), parse_contains)(input).unwrap();
+    println!("Solution: {:?}", run(input));
+}
 
-    let mut result: HashMap<&str, Vec<(usize, &str)>> = HashMap::new();
+/// The bag rules as Graphviz, with everything a shiny gold bag holds picked out.
+pub fn dot() -> Result<String, DagError<&'static str>> {
+    let input = include_str!("input-day7");
+    let (_, all) = separated_list1(tag("\n"), parse_contains)(input.trim()).unwrap();
 
-    for contain in all {
-        match contain {
-            ColorRule::Empty(k) => result.insert(k, vec![]),
-            ColorRule::NonEmpty(k, vs) => result.insert(k, vs),
-        };
-    }
+    Ok(bag_rules(all)
+        .to_dot("bags", Some(&"shiny gold"))?
+        .to_string())
+}
 
-    println!("Solution: {:?}", descendants_of("shiny gold", &result));
+fn run(input: &str) -> Result<(usize, usize), DagError<&str>> {
+    let (_, all) = separated_list1(tag("\n"), parse_contains)(input.trim()).unwrap();
+    let rules = bag_rules(all);
+
+    Ok((
+        rules.ancestors(&"shiny gold")?.len(),
+        rules.weighted_descendants_of(&"shiny gold")?,
+    ))
 }
 
-fn descendants_of<'a, 'b>(
-    color: &'a str,
-    rules: &HashMap<&'b str, Vec<(usize, &'b str)>>,
-) -> usize {
-    if let Some(values) = rules.get(color) {
-        values
-            .iter()
-            .map(|(k, v)| k + k * descendants_of(v, rules))
-            .sum()
-    } else {
-        0
+/// An edge from every bag to each bag it must directly contain, weighted by how many.
+fn bag_rules<'a>(rules: Vec<ColorRule<'a>>) -> Dag<&'a str> {
+    let mut dag = Dag::new();
+
+    for rule in rules {
+        match rule {
+            ColorRule::Empty(bag) => dag.add_node(bag),
+            ColorRule::NonEmpty(bag, contents) => {
+                for (count, inner) in contents {
+                    dag.add_edge(bag, inner, count);
+                }
+            }
+        }
     }
+
+    dag
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn solve_returns_the_correct_value() {
+        let input = r#"
+light red bags contain 1 bright white bag, 2 muted yellow bags.
+dark orange bags contain 3 bright white bags, 4 muted yellow bags.
+bright white bags contain 1 shiny gold bag.
+muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.
+shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.
+dark olive bags contain 3 faded blue bags, 4 dotted black bags.
+vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
+faded blue bags contain no other bags.
+dotted black bags contain no other bags.
+"#;
+        assert_eq!(run(input), Ok((4, 32)))
+    }
+
+    #[test]
+    fn bag_rules_as_dot() {
+        let rules = bag_rules(vec![
+            ColorRule::NonEmpty("light red", vec![(2, "shiny gold")]),
+            ColorRule::NonEmpty("shiny gold", vec![(3, "faded blue")]),
+            ColorRule::Empty("faded blue"),
+        ]);
+        let text = rules
+            .to_dot("bags", Some(&"shiny gold"))
+            .unwrap()
+            .to_string();
+
+        assert!(text.contains("\"light red\" -> \"shiny gold\" [label=\"2\"];"));
+        assert!(text.contains("\"shiny gold\" -> \"faded blue\" [label=\"3\", color=red"));
+    }
+
     #[test]
     fn multiple_rules() {
         assert_eq!(
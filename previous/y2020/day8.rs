@@ -1,62 +1,28 @@
 use crate::parser::*;
+use crate::vm::{
+    self, repair,
+    trace::{disassemble, Tracer},
+    Effect, Halt, Machine, Registers, Swap,
+};
 use nom::{
-    branch::alt, bytes::complete::tag, combinator::map, multi::separated_list1, sequence::preceded,
+    branch::alt,
+    bytes::complete::tag,
+    combinator::{all_consuming, map},
+    multi::separated_list1,
+    sequence::preceded,
     IResult,
 };
+use std::{fmt, io};
 
-struct Computer<'a> {
-    acc: isize,
-    instructions: &'a [Instruction],
-    current_position: usize,
-    previous_positions: Vec<usize>,
-}
-
-impl<'a> Computer<'a> {
-    fn new(instructions: &'a [Instruction]) -> Self {
-        Self {
-            acc: 0,
-            instructions,
-            current_position: 0,
-            previous_positions: vec![],
-        }
-    }
-}
+const ACC: usize = 0;
 
-impl<'a> Iterator for Computer<'a> {
-    type Item = Result<isize, &'static str>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.previous_positions.contains(&self.current_position) {
-            None
-        } else if self.current_position >= self.instructions.len() {
-            None
-        } else {
-            let at_last = self.current_position + 1 == self.instructions.len();
-            self.previous_positions.push(self.current_position);
-            match self.instructions[self.current_position] {
-                Instruction::NoOp(_) => {
-                    self.current_position += 1;
-                }
-                Instruction::JumpToRelative(v) => {
-                    if v < 0 {
-                        self.current_position = self.current_position - v.abs() as usize;
-                    } else {
-                        self.current_position = self.current_position + v as usize;
-                    }
-                }
-                Instruction::IncrementAccumulator(v) => {
-                    self.current_position += 1;
-                    self.acc += v;
-                }
-            }
+/// Runs the boot code until it finishes or is about to repeat an instruction, returning how it
+/// stopped and what the accumulator held at that point.
+fn boot(instructions: &[Instruction]) -> (Halt, isize) {
+    let mut machine = Machine::new(instructions, Registers::new(1));
+    let halt = machine.run();
 
-            if at_last {
-                Some(Ok(self.acc))
-            } else {
-                Some(Err("not done"))
-            }
-        }
-    }
+    (halt, machine.registers()[ACC])
 }
 
 #[derive(Clone)]
@@ -66,21 +32,38 @@ enum Instruction {
     NoOp(isize),
 }
 
-impl Instruction {
-    fn swap(&mut self) -> &mut Self {
-        match self {
+/// Part two's allowed repairs: a `jmp` becomes a `nop` or the other way round.
+const SWAPS: [Swap<Instruction>; 2] = [
+    |instruction| match *instruction {
+        Instruction::JumpToRelative(v) => Some(Instruction::NoOp(v)),
+        _ => None,
+    },
+    |instruction| match *instruction {
+        Instruction::NoOp(v) => Some(Instruction::JumpToRelative(v)),
+        _ => None,
+    },
+];
+
+impl vm::Instruction for Instruction {
+    fn execute(&self, registers: &mut Registers) -> Effect {
+        match *self {
             Instruction::IncrementAccumulator(v) => {
-                *self = Instruction::IncrementAccumulator(*v);
-            }
-            Instruction::JumpToRelative(v) => {
-                *self = Instruction::NoOp(*v);
-            }
-            Instruction::NoOp(v) => {
-                *self = Instruction::JumpToRelative(*v);
+                registers[ACC] += v;
+                Effect::Next
             }
+            Instruction::JumpToRelative(v) => Effect::Jump(v),
+            Instruction::NoOp(_) => Effect::Next,
         }
+    }
+}
 
-        self
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::IncrementAccumulator(v) => write!(f, "acc {:+}", v),
+            Instruction::JumpToRelative(v) => write!(f, "jmp {:+}", v),
+            Instruction::NoOp(v) => write!(f, "nop {:+}", v),
+        }
     }
 }
 
@@ -115,21 +98,73 @@ fn parse_instruction(input: &str) -> IResult<&str, Instruction> {
 
 pub fn solve() {
     let input = include_str!("input-day8");
-    let (_, instructions) = separated_list1(tag("\n"), parse_instruction)(input).unwrap();
-
-    let result = instructions
-        .iter()
-        .enumerate()
-        .filter_map(|(i, _)| {
-            let mut instructions = instructions.clone();
-            instructions[i].swap();
-
-            match Computer::new(&instructions).last() {
-                Some(Ok(v)) => Some(v),
-                _ => None,
-            }
-        })
-        .next();
+    println!("Solution: {:?}", run(input));
+}
+
+/// Prints every step of the boot code up to the loop, then the program with the instructions that
+/// ran marked.
+pub fn trace() -> io::Result<()> {
+    let input = include_str!("input-day8");
+    let (_, instructions) = separated_list1(tag("\n"), parse_instruction)(input.trim()).unwrap();
+    let mut machine = Machine::new(&instructions, Registers::new(1));
+
+    Tracer::new().run(&mut machine)?;
+    print!("{}", disassemble(&machine));
+
+    Ok(())
+}
 
-    println!("Solution: {:?}", result);
+/// Part one is the accumulator just before the first repeated instruction; part two is the
+/// accumulator once the program finishes, after swapping a single `jmp` or `nop`.
+fn run(input: &str) -> Option<(isize, isize)> {
+    let (_, instructions) =
+        all_consuming(separated_list1(tag("\n"), parse_instruction))(input.trim()).ok()?;
+
+    let looped = match boot(&instructions) {
+        (Halt::Loop { .. }, acc) => acc,
+        _ => return None,
+    };
+
+    let repaired = repair(&instructions, Registers::new(1), &SWAPS)?;
+
+    Some((looped, repaired.registers[ACC]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = r#"
+nop +0
+acc +1
+jmp +4
+acc +3
+jmp -3
+acc -99
+acc +1
+jmp -4
+acc +6
+"#;
+
+    #[test]
+    fn solve_returns_the_correct_value() {
+        assert_eq!(super::run(EXAMPLE), Some((5, 8)))
+    }
+
+    #[test]
+    fn disassembling_marks_what_ran() {
+        let (_, instructions) =
+            separated_list1(tag("\n"), parse_instruction)(EXAMPLE.trim()).unwrap();
+        let mut machine = Machine::new(&instructions, Registers::new(1));
+        let mut tracer = Tracer::with_writer(vec![]);
+
+        assert_eq!(tracer.run(&mut machine).unwrap(), Halt::Loop { pc: 1 });
+
+        let listing = disassemble(&machine);
+        let lines = listing.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "   0 * nop +0");
+        assert_eq!(lines[5], "   5   acc -99");
+        assert_eq!(lines[8], "   8   acc +6");
+        assert_eq!(lines.iter().filter(|line| line.contains('*')).count(), 7);
+    }
 }
@@ -1,4 +1,11 @@
+use crate::grid::Grid;
+use crate::ocr::{self, OcrError};
 use crate::parser::*;
+use crate::vm::{
+    self,
+    trace::{disassemble, Tracer},
+    Effect, Machine, Registers,
+};
 use nom::{
     branch::alt,
     bytes::complete::tag,
@@ -7,35 +14,55 @@ use nom::{
     sequence::preceded,
     IResult,
 };
+use std::{fmt, io, ops::ControlFlow};
 
 pub fn solve() {
     let input = include_str!("input-day10");
     println!("Answer: {:?}", run(input));
 }
 
-fn run(input: &str) -> Option<isize> {
+/// Prints every instruction with the cycle it starts on and `x` before and after, then the
+/// program with the instructions that ran marked.
+pub fn trace() -> io::Result<()> {
+    let input = include_str!("input-day10");
+    let (_, instructions) = separated_list1(tag("\n"), parse_instruction)(input.trim()).unwrap();
+    let mut machine = Machine::new(&instructions, Registers::from(vec![X_START]));
+
+    Tracer::new().run(&mut machine)?;
+    print!("{}", disassemble(&machine));
+
+    Ok(())
+}
+
+/// Part one sums the signal strength at cycles 20, 60, ..., 220 (if the program runs that long);
+/// part two is the letters the CRT draws, which are read separately so a screen that isn't
+/// letters doesn't lose part one.
+fn run(input: &str) -> Result<(Option<isize>, Result<String, OcrError>), CpuError> {
     let (_, instructions) =
-        all_consuming(separated_list1(tag("\n"), parse_instruction))(input.trim()).ok()?;
+        all_consuming(separated_list1(tag("\n"), parse_instruction))(input.trim())
+            .map_err(|_| CpuError::Unparseable)?;
     let cpu = Cpu::process(instructions);
 
-    for (index, state) in cpu.cycles.iter().enumerate() {
-        let idx = (index as isize) % 40;
-        let range = (state.x - 1)..=(state.x + 1);
+    let strength = cpu.signal_strength((20..=220).step_by(40));
 
-        if idx == 0 {
-            print!("\n");
-        }
+    Ok((strength, ocr::recognize(&cpu.screen())))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum CpuError {
+    Unparseable,
+}
 
-        if range.contains(&idx) {
-            print!("#");
-        } else {
-            print!(".");
+impl fmt::Display for CpuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CpuError::Unparseable => write!(f, "couldn't read the program"),
         }
     }
-
-    None
 }
 
+impl std::error::Error for CpuError {}
+
 #[derive(PartialEq, Clone, Debug, Copy)]
 enum Instruction {
     NoOp,
@@ -43,48 +70,78 @@ enum Instruction {
 }
 
 #[derive(Debug)]
-struct State {
-    cycle: usize,
-    x: isize,
+struct Cpu {
+    /// The value of `x` during each cycle, first cycle first.
+    cycles: Vec<isize>,
 }
 
-#[derive(Debug)]
-struct Cpu {
-    cycles: Vec<State>,
+const X: usize = 0;
+const X_START: isize = 1;
+
+impl vm::Instruction for Instruction {
+    fn cycles(&self) -> usize {
+        match self {
+            Instruction::NoOp => 1,
+            Instruction::AddX(_) => 2,
+        }
+    }
+
+    fn execute(&self, registers: &mut Registers) -> Effect {
+        if let Instruction::AddX(v) = self {
+            registers[X] += v;
+        }
+
+        Effect::Next
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::NoOp => write!(f, "noop"),
+            Instruction::AddX(v) => write!(f, "addx {}", v),
+        }
+    }
 }
 
 impl Cpu {
     fn process(instructions: Vec<Instruction>) -> Self {
         let mut cpu = Cpu { cycles: vec![] };
+        let mut machine = Machine::new(&instructions, Registers::from(vec![X_START]));
 
-        let mut instructions_with_steps = vec![];
-
-        for instruction in instructions {
-            if instruction != Instruction::NoOp {
-                instructions_with_steps.push(None);
-            }
-
-            instructions_with_steps.push(Some(instruction));
-        }
+        machine.run_with(|tick| {
+            cpu.cycles.push(tick.registers[X]);
+            ControlFlow::Continue(())
+        });
 
-        let mut cycle = 1;
-        let mut during_x = 1;
+        cpu
+    }
 
-        for instruction in instructions_with_steps {
-            let x = during_x;
-            cpu.cycles.push(State { cycle, x });
+    /// The value of `x` during `cycle`, counting from 1, or `None` once the program has ended.
+    fn x_during(&self, cycle: usize) -> Option<isize> {
+        self.cycles.get(cycle.checked_sub(1)?).copied()
+    }
 
-            match instruction {
-                Some(Instruction::AddX(x)) => {
-                    during_x += x;
-                }
-                Some(Instruction::NoOp) | None => (),
-            }
+    /// The sum of `cycle * x` over the given cycles, or `None` if the program ends before one of
+    /// them.
+    fn signal_strength(&self, cycles: impl IntoIterator<Item = usize>) -> Option<isize> {
+        cycles
+            .into_iter()
+            .map(|cycle| Some(cycle as isize * self.x_during(cycle)?))
+            .sum()
+    }
 
-            cycle += 1;
-        }
+    /// The CRT, 40 pixels to a row. The beam draws one pixel per cycle, left to right, and it's
+    /// lit if the three-pixel-wide sprite centred on `x` covers it.
+    fn screen(&self) -> Grid<bool> {
+        let pixels = self
+            .cycles
+            .iter()
+            .enumerate()
+            .map(|(index, x)| ((index % 40) as isize - x).abs() <= 1)
+            .collect::<Vec<_>>();
 
-        cpu
+        Grid::new(pixels.chunks(40).map(<[bool]>::to_vec).collect())
     }
 }
 
@@ -95,12 +152,11 @@ fn parse_instruction(input: &str) -> IResult<&str, Instruction> {
     ))(input)
 }
 
+#[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn solve_returns_the_correct_value() {
-        let input = r#"
+    const EXAMPLE: &str = r#"
 addx 15
 addx -11
 addx 6
@@ -247,7 +303,43 @@ addx -11
 noop
 noop
 noop
-        "#;
+"#;
+
+    #[test]
+    fn solve_returns_the_correct_value() {
+        let (_, instructions) =
+            all_consuming(separated_list1(tag("\n"), parse_instruction))(EXAMPLE.trim()).unwrap();
+        let cpu = Cpu::process(instructions);
+        let screen = cpu.screen();
+        let rows = screen
+            .rows()
+            .iter()
+            .map(|row| row.iter().map(|&lit| if lit { '#' } else { '.' }).collect())
+            .collect::<Vec<String>>();
+
+        assert_eq!(
+            rows,
+            vec![
+                "##..##..##..##..##..##..##..##..##..##..",
+                "###...###...###...###...###...###...###.",
+                "####....####....####....####....####....",
+                "#####.....#####.....#####.....#####.....",
+                "######......######......######......####",
+                "#######.......#######.......#######.....",
+            ]
+        );
+        assert_eq!(
+            [20, 60, 100, 140, 180, 220].map(|cycle| cpu.x_during(cycle)),
+            [21, 19, 18, 21, 16, 18].map(Some)
+        );
+        assert_eq!(cpu.x_during(0), None);
+        assert_eq!(cpu.signal_strength((20..=220).step_by(40)), Some(13140));
+        assert_eq!(cpu.signal_strength([20, 1000]), None);
+        assert!(matches!(
+            super::run(EXAMPLE),
+            Ok((Some(13140), Err(OcrError::UnknownGlyph { position: 0, .. })))
+        ));
+        assert_eq!(super::run("noop\naddx"), Err(CpuError::Unparseable));
     }
 
     #[test]
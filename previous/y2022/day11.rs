@@ -1,5 +1,5 @@
-use std::collections::BTreeMap;
-
+use crate::cycle::{self, Cycle};
+use crate::math;
 use crate::parser::*;
 use itertools::Itertools;
 use nom::{
@@ -10,77 +10,319 @@ use nom::{
     sequence::{preceded, separated_pair, terminated},
     IResult,
 };
+use std::fmt;
 
 pub fn solve() {
     let input = include_str!("input-day11");
     println!("Answer: {:?}", run(input));
 }
 
-fn times(n: usize) -> impl Iterator {
-    std::iter::repeat(()).take(n)
+/// Prints the running inspection counts without relief at the same checkpoints the puzzle shows:
+/// after rounds 1 and 20, then every thousandth.
+pub fn report() -> Result<(), MonkeyError> {
+    let input = include_str!("input-day11");
+    let monkeys = parse_monkeys(input)?;
+    let sim = MonkeySim::new(monkeys, 10000, Relief::None)?;
+    let mut totals = vec![0; sim.monkeys.len()];
+
+    for (idx, counts) in sim.inspections_per_round()?.iter().enumerate() {
+        let round = idx + 1;
+
+        for (total, count) in totals.iter_mut().zip(counts) {
+            *total += count;
+        }
+
+        if round == 1 || round == 20 || round % 1000 == 0 {
+            println!("== After round {} ==", round);
+
+            for (id, total) in totals.iter().enumerate() {
+                println!("Monkey {} inspected items {} times.", id, total);
+            }
+        }
+    }
+
+    Ok(())
 }
 
-fn run(input: &str) -> Option<usize> {
-    let (_, monkey_list) =
-        all_consuming(separated_list1(many1(tag("\n")), parse_monkey))(input.trim()).ok()?;
+/// Monkey business after 20 rounds with relief, then after 10,000 without.
+fn run(input: &str) -> Result<(usize, usize), MonkeyError> {
+    let monkeys = parse_monkeys(input)?;
 
-    let mut monkeys = BTreeMap::new();
+    let relieved = MonkeySim::new(monkeys.clone(), 20, Relief::DivideBy(3))?;
+    let worried = MonkeySim::new(monkeys, 10000, Relief::None)?;
 
-    let keys = monkey_list.iter().map(|m| m.id).collect::<Vec<_>>();
+    Ok((relieved.monkey_business()?, worried.monkey_business()?))
+}
 
-    for monkey in monkey_list.into_iter() {
-        monkeys.insert(monkey.id, monkey);
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MonkeyError {
+    Unparseable,
+    /// Monkey business takes the two busiest monkeys, so there have to be at least two.
+    TooFewMonkeys(usize),
+    /// Monkeys have to be listed in order, numbered from 0.
+    Misnumbered {
+        position: usize,
+        id: usize,
+    },
+    /// Monkey `from` throws to a monkey that isn't there.
+    UnknownTarget {
+        from: usize,
+        to: usize,
+    },
+    /// A test or the relief divides by zero.
+    DivisionByZero,
+    /// An item got too worrying to count, within the rounds played.
+    Overflow,
+}
+
+impl fmt::Display for MonkeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MonkeyError::Unparseable => write!(f, "couldn't read the monkey notes"),
+            MonkeyError::TooFewMonkeys(count) => {
+                write!(f, "need at least two monkeys, found {}", count)
+            }
+            MonkeyError::Misnumbered { position, id } => {
+                write!(f, "monkey {} is listed in position {}", id, position)
+            }
+            MonkeyError::UnknownTarget { from, to } => {
+                write!(f, "monkey {} throws to missing monkey {}", from, to)
+            }
+            MonkeyError::DivisionByZero => write!(f, "division by zero"),
+            MonkeyError::Overflow => write!(f, "a worry level overflowed"),
+        }
     }
+}
 
-    let base: usize = monkeys.values().map(|m| m.test.divisible_by).product();
+impl std::error::Error for MonkeyError {}
 
-    for _ in times(10000) {
-        for id in &keys {
-            let mut throw_at: Vec<(usize, Item)> = vec![];
-            let monkey = monkeys.get_mut(&id).unwrap();
+/// What happens to an item's worry level once a monkey has inspected it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Relief {
+    /// It's divided by this much (rounding down), since the item wasn't damaged.
+    DivideBy(usize),
+    /// Nothing, so worry levels are kept modulo the lcm of the tests' divisors instead, which
+    /// leaves every test's outcome the same.
+    None,
+}
 
-            for item in monkey.items.drain(0..) {
-                monkey.inspection_count += 1;
-                let new_item = Item(monkey.operation.run(item.0) % base);
+/// Where an item is at the start of a round: which monkey holds it, and how worrying it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Held {
+    monkey: usize,
+    worry: usize,
+}
 
-                throw_at.push(monkey.test.throw(new_item));
+/// One item's running inspection counts per monkey, from the start of the game.
+struct Tally {
+    cycle: Option<Cycle>,
+    /// After every round played. If the item's state repeats, that's only until the first repeat.
+    totals: Vec<Vec<usize>>,
+}
+
+impl Tally {
+    fn after(&self, round: usize, monkey: usize) -> usize {
+        let total_at = |round: usize| self.totals[round][monkey] as isize;
+
+        match self.cycle {
+            Some(cycle) => cycle.extrapolate(round, total_at) as usize,
+            None => total_at(round) as usize,
+        }
+    }
+}
+
+/// The monkeys playing keep-away for a fixed number of rounds.
+///
+/// Items never affect each other, so each is followed on its own, and a single item's state is
+/// small enough that it usually starts repeating. Once it does, its counts go up by the same
+/// amount every lap and the remaining rounds are extrapolated instead of played.
+struct MonkeySim {
+    monkeys: Vec<Monkey>,
+    rounds: usize,
+    relief: Relief,
+    modulus: usize,
+}
+
+impl MonkeySim {
+    fn new(monkeys: Vec<Monkey>, rounds: usize, relief: Relief) -> Result<Self, MonkeyError> {
+        if monkeys.len() < 2 {
+            return Err(MonkeyError::TooFewMonkeys(monkeys.len()));
+        }
+
+        for (position, monkey) in monkeys.iter().enumerate() {
+            if monkey.id != position {
+                return Err(MonkeyError::Misnumbered {
+                    position,
+                    id: monkey.id,
+                });
+            }
+
+            let test = &monkey.test;
+
+            if let Some(&to) = [test.when_true, test.when_false]
+                .iter()
+                .find(|&&to| to >= monkeys.len())
+            {
+                return Err(MonkeyError::UnknownTarget {
+                    from: monkey.id,
+                    to,
+                });
             }
 
-            for (id, item) in throw_at {
-                monkeys.get_mut(&id).unwrap().items.push(item);
+            if test.divisible_by == 0 {
+                return Err(MonkeyError::DivisionByZero);
             }
         }
+
+        if relief == Relief::DivideBy(0) {
+            return Err(MonkeyError::DivisionByZero);
+        }
+
+        let modulus = math::lcm_all(monkeys.iter().map(|monkey| monkey.test.divisible_by));
+
+        Ok(MonkeySim {
+            monkeys,
+            rounds,
+            relief,
+            modulus,
+        })
     }
 
-    let (v1, v2) = if let [v1, v2] = monkeys
-        .values()
-        .sorted_by_key(|m| m.inspection_count)
-        .map(|m| m.inspection_count)
-        .rev()
-        .take(2)
-        .collect::<Vec<_>>()[..]
-    {
-        (v1, v2)
-    } else {
-        todo!()
-    };
+    /// Follows an item through one round, calling `inspected` for every monkey that handles it.
+    /// Monkeys take their turns in order, so an item thrown to a later monkey moves again in the
+    /// same round, while one thrown back to itself or an earlier monkey waits for the next.
+    ///
+    /// Gives `None` if the item's worry level overflows.
+    fn round(&self, held: Held, mut inspected: impl FnMut(usize)) -> Option<Held> {
+        let mut held = held;
+
+        loop {
+            let monkey = &self.monkeys[held.monkey];
+            inspected(monkey.id);
+
+            let worry = monkey.operation.run(held.worry)?;
+            let worry = match self.relief {
+                Relief::DivideBy(divisor) => worry / divisor,
+                Relief::None => worry % self.modulus,
+            };
+            let (to, item) = monkey.test.throw(Item(worry));
+            let thrown = Held {
+                monkey: to,
+                worry: item.0,
+            };
+
+            if to <= held.monkey {
+                return Some(thrown);
+            }
 
-    Some(v1 * v2)
+            held = thrown;
+        }
+    }
+
+    fn tally(&self, held: Held) -> Result<Tally, MonkeyError> {
+        // an overflow only matters if it happens within the rounds actually played, so while
+        // looking for a cycle it's just one more state
+        let cycle = cycle::brent(
+            &Some(held),
+            |held| held.and_then(|held| self.round(held, |_| ())),
+            |&held| held,
+            2 * self.rounds,
+        );
+        let played = cycle.map_or(self.rounds, |cycle| {
+            (cycle.start + cycle.length).min(self.rounds)
+        });
+
+        let mut totals = vec![vec![0; self.monkeys.len()]];
+        let mut current = held;
+
+        for _ in 0..played {
+            let mut counts = totals[totals.len() - 1].clone();
+            current = self
+                .round(current, |id| counts[id] += 1)
+                .ok_or(MonkeyError::Overflow)?;
+            totals.push(counts);
+        }
+
+        Ok(Tally { cycle, totals })
+    }
+
+    fn tallies(&self) -> Result<Vec<Tally>, MonkeyError> {
+        self.monkeys
+            .iter()
+            .flat_map(|monkey| {
+                monkey.items.iter().map(|item| Held {
+                    monkey: monkey.id,
+                    worry: item.0,
+                })
+            })
+            .map(|held| self.tally(held))
+            .collect()
+    }
+
+    /// How many items each monkey inspected in each round, first round first.
+    fn inspections_per_round(&self) -> Result<Vec<Vec<usize>>, MonkeyError> {
+        let tallies = self.tallies()?;
+
+        let per_round = (1..=self.rounds)
+            .map(|round| {
+                (0..self.monkeys.len())
+                    .map(|monkey| {
+                        tallies
+                            .iter()
+                            .map(|tally| {
+                                tally.after(round, monkey) - tally.after(round - 1, monkey)
+                            })
+                            .sum()
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Ok(per_round)
+    }
+
+    /// How many items each monkey inspected over the whole game.
+    fn inspections(&self) -> Result<Vec<usize>, MonkeyError> {
+        let tallies = self.tallies()?;
+
+        let totals = (0..self.monkeys.len())
+            .map(|monkey| {
+                tallies
+                    .iter()
+                    .map(|tally| tally.after(self.rounds, monkey))
+                    .sum()
+            })
+            .collect();
+
+        Ok(totals)
+    }
+
+    /// The inspection counts of the two busiest monkeys, multiplied.
+    fn monkey_business(&self) -> Result<usize, MonkeyError> {
+        let business = self
+            .inspections()?
+            .into_iter()
+            .sorted()
+            .rev()
+            .take(2)
+            .product();
+
+        Ok(business)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Monkey {
     id: usize,
     items: Vec<Item>,
     operation: Operation,
     test: Throw,
-    inspection_count: usize,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Item(usize);
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Throw {
     divisible_by: usize,
     when_true: usize,
@@ -112,21 +354,29 @@ impl Value {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 enum Operation {
     Add(Value, Value),
     Multiply(Value, Value),
 }
 
 impl Operation {
-    fn run(&self, old_value: usize) -> usize {
+    /// The new worry level, or `None` if it doesn't fit.
+    fn run(&self, old_value: usize) -> Option<usize> {
         match self {
-            Operation::Add(l, r) => l.resolve(old_value) + r.resolve(old_value),
-            Operation::Multiply(l, r) => l.resolve(old_value) * r.resolve(old_value),
+            Operation::Add(l, r) => l.resolve(old_value).checked_add(r.resolve(old_value)),
+            Operation::Multiply(l, r) => l.resolve(old_value).checked_mul(r.resolve(old_value)),
         }
     }
 }
 
+fn parse_monkeys(input: &str) -> Result<Vec<Monkey>, MonkeyError> {
+    let (_, monkeys) = all_consuming(separated_list1(many1(tag("\n")), parse_monkey))(input.trim())
+        .map_err(|_| MonkeyError::Unparseable)?;
+
+    Ok(monkeys)
+}
+
 fn parse_monkey(input: &str) -> IResult<&str, Monkey> {
     let (input, id) = terminated(parse_monkey_id, tag("\n"))(input)?;
     let (input, items) = terminated(parse_starting_items, tag("\n"))(input)?;
@@ -140,7 +390,6 @@ fn parse_monkey(input: &str) -> IResult<&str, Monkey> {
             items,
             operation,
             test,
-            inspection_count: 0,
         },
     ))
 }
@@ -200,10 +449,11 @@ fn parse_throw(input: &str) -> IResult<&str, Throw> {
     ))
 }
 
+#[cfg(test)]
 mod tests {
-    #[test]
-    fn solve_returns_the_correct_value() {
-        let input = r#"
+    use super::*;
+
+    const EXAMPLE: &str = r#"
 Monkey 0:
   Starting items: 79, 98
   Operation: new = old * 19
@@ -232,6 +482,86 @@ Monkey 3:
     If true: throw to monkey 0
     If false: throw to monkey 1
 "#;
-        assert_eq!(super::run(input), Some(2713310158))
+
+    #[test]
+    fn solve_returns_the_correct_value() {
+        assert_eq!(super::run(EXAMPLE), Ok((10605, 2713310158)))
+    }
+
+    #[test]
+    fn counting_inspections() {
+        let relieved =
+            MonkeySim::new(parse_monkeys(EXAMPLE).unwrap(), 20, Relief::DivideBy(3)).unwrap();
+        assert_eq!(relieved.inspections(), Ok(vec![101, 95, 7, 105]));
+
+        let worried = MonkeySim::new(parse_monkeys(EXAMPLE).unwrap(), 20, Relief::None).unwrap();
+        let per_round = worried.inspections_per_round().unwrap();
+        assert_eq!(per_round.len(), 20);
+        assert_eq!(per_round[0], vec![2, 4, 3, 6]);
+
+        let totals = (0..4)
+            .map(|monkey| per_round.iter().map(|round| round[monkey]).sum())
+            .collect::<Vec<usize>>();
+        assert_eq!(totals, vec![99, 97, 8, 103]);
+        assert_eq!(worried.inspections(), Ok(totals));
+    }
+
+    /// Monkey 0 keeps throwing its one item to itself.
+    fn juggler(operation: Operation) -> Vec<Monkey> {
+        let monkey = |id, items| Monkey {
+            id,
+            items,
+            operation: operation.clone(),
+            test: Throw {
+                divisible_by: 2,
+                when_true: 0,
+                when_false: 1,
+            },
+        };
+
+        vec![monkey(0, vec![Item(2)]), monkey(1, vec![])]
+    }
+
+    #[test]
+    fn items_thrown_to_the_same_monkey_wait_a_round() {
+        let adding = juggler(Operation::Add(Value::Old, Value::Actual(0)));
+        let sim = MonkeySim::new(adding, 20, Relief::None).unwrap();
+
+        assert_eq!(sim.inspections(), Ok(vec![20, 0]));
+    }
+
+    #[test]
+    fn overflowing_worry_is_an_error() {
+        let squaring = juggler(Operation::Multiply(Value::Old, Value::Old));
+
+        let sim = MonkeySim::new(squaring.clone(), 20, Relief::DivideBy(1)).unwrap();
+        assert_eq!(sim.inspections(), Err(MonkeyError::Overflow));
+
+        // only the rounds asked for count, not the ones looked at while hunting for a cycle
+        let sim = MonkeySim::new(squaring, 5, Relief::DivideBy(1)).unwrap();
+        assert_eq!(sim.inspections(), Ok(vec![5, 0]));
+    }
+
+    #[test]
+    fn degenerate_notes_are_errors() {
+        let all = parse_monkeys(EXAMPLE).unwrap();
+
+        assert!(matches!(
+            MonkeySim::new(all[..1].to_vec(), 20, Relief::None),
+            Err(MonkeyError::TooFewMonkeys(1))
+        ));
+        assert!(matches!(
+            MonkeySim::new(all[..2].to_vec(), 20, Relief::None),
+            Err(MonkeyError::UnknownTarget { from: 0, to: 2 })
+        ));
+        assert!(matches!(
+            MonkeySim::new(all[1..].to_vec(), 20, Relief::None),
+            Err(MonkeyError::Misnumbered { position: 0, id: 1 })
+        ));
+        assert!(matches!(
+            MonkeySim::new(all, 20, Relief::DivideBy(0)),
+            Err(MonkeyError::DivisionByZero)
+        ));
+        assert_eq!(super::run("Monkey 0:"), Err(MonkeyError::Unparseable));
     }
 }
@@ -1,213 +1,261 @@
-use colored::Colorize;
-use petgraph::algo::dijkstra;
-use petgraph::graph::{Graph, NodeIndex};
-use std::collections::BTreeMap;
+use crate::dot::Dot;
+use crate::geometry::{direction::Direction4, Point2};
+use crate::grid::Grid;
+use crate::search::{bfs, Explored};
+use crate::visualizer::{self, Cell, Frame, Rgb, Visualizer};
 
 pub fn solve() {
     let input = include_str!("input-day12");
     println!("Answer: {:?}", run(input));
 }
 
-#[derive(Clone, Copy, Debug)]
-struct Point(usize, usize);
-
-impl Position {
-    fn from_char(idx: PositionIdx, input: char, point: Point) -> Self {
-        match input {
-            'S' => Position::Starting(idx, point),
-            'E' => Position::Ending(idx, point),
-            v => Position::Point(idx, point, v),
-        }
-    }
-
-    fn edges_to(&self, other: &Position) -> Vec<(PositionIdx, PositionIdx)> {
-        let mut results = vec![];
+/// Draws the shortest hike from the lowest ground up to `E`.
+pub fn animate(visualizer: &mut impl Visualizer) -> std::io::Result<()> {
+    let input = include_str!("input-day12");
 
-        if self.height() - other.height() > 1 {
-            results.push((other.index(), self.index()));
-        }
+    if let Some(map) = HeightMap::parse(input) {
+        let descent = map.descend();
 
-        if other.height() - self.height() > 1 {
-            results.push((self.index(), other.index()));
+        if let Some(path) = map.scenic_trail(&descent) {
+            visualizer::record(visualizer, || map.render(&path));
         }
+    }
 
-        if (self.height() - other.height()).abs() <= 1 {
-            results.push((other.index(), self.index()));
-            results.push((self.index(), other.index()));
-        }
+    visualizer.finish()
+}
 
-        results
-    }
+/// Every climbable step as Graphviz, with the part two hike picked out.
+pub fn dot() -> Option<String> {
+    let input = include_str!("input-day12");
+    let map = HeightMap::parse(input)?;
+    let trail = map.scenic_trail(&map.descend())?;
 
-    fn to_char(&self) -> char {
-        match self {
-            Position::Starting(_, _) => 'S',
-            Position::Ending(_, _) => 'E',
-            Position::Point(_, _, v) => *v,
-        }
-    }
+    Some(map.to_dot(&trail).to_string())
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-struct PositionIdx(usize);
+type Point = Point2<usize>;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 enum Position {
-    Starting(PositionIdx, Point),
-    Point(PositionIdx, Point, char),
-    Ending(PositionIdx, Point),
+    Starting,
+    Point(char),
+    Ending,
 }
 
 impl Position {
-    fn starting(&self) -> bool {
-        match self {
-            Position::Starting(_, _) => true,
-            _ => false,
-        }
-    }
-
-    fn ending(&self) -> bool {
-        match self {
-            Position::Ending(_, _) => true,
-            _ => false,
+    fn from_char(input: char) -> Self {
+        match input {
+            'S' => Position::Starting,
+            'E' => Position::Ending,
+            v => Position::Point(v),
         }
     }
 
     fn height(&self) -> isize {
         match self {
-            Position::Starting(_, _) => 0,
-            Position::Ending(_, _) => 25,
-            Position::Point(_, _, v) => (*v as u8 - 97) as isize,
+            Position::Starting => 0,
+            Position::Ending => 25,
+            Position::Point(v) => (*v as u8 - 97) as isize,
         }
     }
 
-    fn index(&self) -> PositionIdx {
+    fn to_char(self) -> char {
         match self {
-            Position::Starting(v, _) => *v,
-            Position::Ending(v, _) => *v,
-            Position::Point(v, _, _) => *v,
+            Position::Starting => 'S',
+            Position::Ending => 'E',
+            Position::Point(v) => v,
         }
     }
-}
-
-fn horizontal_edges(positions: Vec<Position>) -> Vec<(PositionIdx, PositionIdx)> {
-    let mut results = vec![];
 
-    for pair in positions.as_slice().windows(2) {
-        results.extend(pair[0].edges_to(&pair[1]));
+    /// The climbing rule run backwards: whether someone standing on `other` could have stepped up
+    /// (or down) onto this position.
+    fn reachable_from(&self, other: &Position) -> bool {
+        self.height() - other.height() <= 1
     }
+}
 
-    results
+struct HeightMap {
+    grid: Grid<Position>,
+    start: Point,
+    end: Point,
 }
 
-fn vertical_edges(top: Vec<Position>, bottom: Vec<Position>) -> Vec<(PositionIdx, PositionIdx)> {
-    if top.is_empty() {
-        return vec![];
+impl HeightMap {
+    fn parse(input: &str) -> Option<Self> {
+        let grid = Grid::new(
+            input
+                .trim()
+                .lines()
+                .map(|line| line.chars().map(Position::from_char).collect())
+                .collect(),
+        );
+        let find = |wanted: Position| {
+            grid.iter()
+                .find(|&(_, &position)| position == wanted)
+                .map(|(point, _)| point)
+        };
+
+        Some(HeightMap {
+            start: find(Position::Starting)?,
+            end: find(Position::Ending)?,
+            grid,
+        })
     }
 
-    let mut results = vec![];
+    /// Walks downhill from `E` to everywhere that could climb up to it, so one search answers
+    /// both "from `S`" and "from the nearest low point".
+    fn descend(&self) -> Explored<Point, usize> {
+        bfs(
+            [self.end],
+            |&point| {
+                let here = self
+                    .grid
+                    .get(point)
+                    .expect("only points on the map are queued");
+
+                Direction4::all()
+                    .filter_map(move |direction| direction.step(point))
+                    .filter(move |&next| {
+                        self.grid
+                            .get(next)
+                            .is_some_and(|there| here.reachable_from(there))
+                    })
+            },
+            |_| false,
+        )
+    }
 
-    for (idx, position) in bottom.iter().enumerate() {
-        results.extend(position.edges_to(&top[idx]));
+    /// The closest point at the lowest height. The search settles points nearest first, so the
+    /// first low point it reached is the best one.
+    fn nearest_low_point(&self, descent: &Explored<Point, usize>) -> Option<Point> {
+        descent.visited().iter().copied().find(|&point| {
+            self.grid
+                .get(point)
+                .is_some_and(|position| position.height() == 0)
+        })
     }
 
-    results
-}
+    /// The part two hike, in walking order from the low point up to `E`.
+    fn scenic_trail(&self, descent: &Explored<Point, usize>) -> Option<Vec<Point>> {
+        let mut path = descent.path_to(&self.nearest_low_point(descent)?)?;
+        path.reverse();
+        Some(path)
+    }
 
-fn run(input: &str) -> Option<usize> {
-    let matrix = input
-        .trim()
-        .lines()
-        .map(|line| line.chars().collect::<Vec<_>>())
-        .collect::<Vec<_>>();
-
-    let mut idx = 0;
-    let mut previous_row = vec![];
-    let mut result = BTreeMap::new();
-    let mut edges: Vec<(PositionIdx, PositionIdx)> = vec![];
-    let mut ending = None;
-    let mut grid = vec![];
-
-    for (row_idx, row) in matrix.iter().enumerate() {
-        let mut current_row = vec![];
-
-        for (col_idx, col) in row.iter().enumerate() {
-            let position = Position::from_char(PositionIdx(idx), *col, Point(row_idx, col_idx));
-            current_row.push(position);
-
-            if position.ending() {
-                ending = Some(position);
+    /// The height map shaded from dark (low) to light (high), with `path` drawn over it as arrows.
+    fn render(&self, path: &[Point]) -> Frame {
+        let mut frame = Frame::from_rows(self.grid.rows(), |position| {
+            let shade = (40 + position.height() * 8) as u8;
+            match position {
+                Position::Ending => Cell::new('E', Rgb(230, 80, 80)),
+                _ => Cell::new('.', Rgb(shade, shade, shade)),
+            }
+        });
+
+        for pair in path.windows(2) {
+            let arrow = Direction4::all()
+                .find(|direction| direction.step(pair[0]) == Some(pair[1]))
+                .map(|direction| match direction {
+                    Direction4::North => '^',
+                    Direction4::East => '>',
+                    Direction4::South => 'v',
+                    Direction4::West => '<',
+                });
+
+            if let Some(arrow) = arrow {
+                frame.set(pair[0].x, pair[0].y, Cell::new(arrow, Rgb(240, 200, 60)));
             }
-            result.insert(idx, position);
-
-            idx += 1;
         }
 
-        grid.push(current_row.clone());
-        edges.extend(horizontal_edges(current_row.clone()));
-        edges.extend(vertical_edges(previous_row, current_row.clone()));
-        previous_row = current_row;
+        frame
     }
 
-    let e = edges
-        .iter()
-        .map(|e| (NodeIndex::new(e.0 .0), NodeIndex::new(e.1 .0)))
-        .collect::<Vec<_>>();
+    /// One node per square, labelled with its height, and an edge for every step that can be
+    /// climbed, with `path` highlighted.
+    fn to_dot(&self, path: &[Point]) -> Dot {
+        let id = |point: Point| format!("{},{}", point.x, point.y);
+        let mut dot = Dot::digraph("heights");
 
-    let g = Graph::<i32, ()>::from_edges(&e);
-    let node_map = dijkstra(&g, NodeIndex::new(ending.unwrap().index().0), None, |_| 1);
+        for (point, position) in self.grid.iter() {
+            dot.node(id(point), position.to_char());
+        }
 
-    let mut results = node_map
-        .iter()
-        .map(|(idx, distance)| {
-            let pos = result.get(&idx.index()).unwrap();
-            (pos.height(), distance, pos)
-        })
-        .collect::<Vec<_>>();
+        for (point, position) in self.grid.iter() {
+            for next in Direction4::all().filter_map(|direction| direction.step(point)) {
+                if self
+                    .grid
+                    .get(next)
+                    .is_some_and(|there| there.reachable_from(position))
+                {
+                    dot.edge(id(point), id(next));
+                }
+            }
+        }
 
-    results.sort_by_key(|x| (x.0, x.1));
+        dot.highlight_path(path.iter().map(|&point| id(point)));
+        dot
+    }
+}
 
-    let lowest = results.iter().map(|v| v.0).min();
-    let binding = results
-        .iter()
-        .filter(|r| r.0 == lowest.unwrap())
-        .collect::<Vec<_>>();
+fn run(input: &str) -> Option<(usize, usize)> {
+    let map = HeightMap::parse(input)?;
+    let descent = map.descend();
 
-    node_map
-        .get(&NodeIndex::new(binding[0].2.index().0))
-        .copied()
+    Some((
+        descent.cost(&map.start)?,
+        descent.cost(&map.nearest_low_point(&descent)?)?,
+    ))
 }
 
+#[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn solve_returns_the_correct_value() {
-        let input = r#"
+    const EXAMPLE: &str = r#"
 Sabqponm
 abcryxxl
 accszExk
 acctuvwj
 abdefghi
 "#;
-        assert_eq!(run(input), Some(29))
+
+    #[test]
+    fn solve_returns_the_correct_value() {
+        assert_eq!(run(EXAMPLE), Some((31, 29)))
     }
 
     #[test]
     fn test_position() {
-        assert_eq!(
-            Position::from_char(PositionIdx(0), 'a', Point(0, 0)).height(),
-            0
-        );
+        assert_eq!(Position::from_char('a').height(), 0);
 
-        assert_eq!(
-            Position::from_char(PositionIdx(0), 'z', Point(0, 0)).height(),
-            25
-        );
+        assert_eq!(Position::from_char('z').height(), 25);
 
+        assert_eq!(Position::from_char('S').height(), 0);
+    }
+
+    #[test]
+    fn rendering_the_trail() {
+        let map = HeightMap::parse(EXAMPLE).unwrap();
+        let trail = map.scenic_trail(&map.descend()).unwrap();
+        let text = map.render(&trail).to_text();
+
+        assert_eq!(trail.last(), Some(&map.end));
+        assert_eq!(text.chars().filter(|c| "^>v<".contains(*c)).count(), 29);
         assert_eq!(
-            Position::from_char(PositionIdx(0), 'S', Point(0, 0)).height(),
-            0
+            text.lines().nth(2).map(|line| line.contains('E')),
+            Some(true)
         );
     }
+
+    #[test]
+    fn dot_follows_the_climbing_rule() {
+        let map = HeightMap::parse(EXAMPLE).unwrap();
+        let trail = map.scenic_trail(&map.descend()).unwrap();
+        let text = map.to_dot(&trail).to_string();
+
+        assert!(text.contains("\"0,0\" [label=\"S\""));
+        assert!(text.contains("\"3,1\" -> \"3,2\""));
+        assert!(!text.contains("\"2,1\" -> \"3,1\""));
+        assert_eq!(text.matches("penwidth").count(), 29);
+    }
 }
@@ -1,4 +1,6 @@
+use crate::geometry::{direction::Direction8, line::polyline, Point2};
 use crate::parser::*;
+use crate::visualizer::{self, Cell, Frame, Rgb, Visualizer};
 use nom::{
     bytes::complete::tag,
     combinator::{all_consuming, map},
@@ -6,36 +8,45 @@ use nom::{
     sequence::separated_pair,
     IResult,
 };
-use std::cmp::Ordering;
 
 pub fn solve() {
     let input = include_str!("input-day14");
     println!("Answer: {:?}", run(input));
 }
 
+pub fn animate(visualizer: &mut impl Visualizer) -> std::io::Result<()> {
+    let input = include_str!("input-day14");
+    simulate(input, visualizer);
+
+    visualizer.finish()
+}
+
 fn run(input: &str) -> Option<usize> {
+    simulate(input, &mut ())
+}
+
+fn simulate(input: &str, visualizer: &mut impl Visualizer) -> Option<usize> {
     let mut walls = vec![];
 
     let (_, all_points) =
         all_consuming(separated_list1(tag("\n"), parse_line))(input.trim()).ok()?;
 
     for points in &all_points {
-        for pair in points.as_slice().windows(2) {
-            walls.extend(pair[0].to_position(pair[1]));
-        }
+        walls.extend(polyline(points));
     }
 
     let maximum_y = &all_points
         .iter()
         .flatten()
-        .map(|position| position.1)
+        .map(|position| position.y)
         .max()
         .unwrap()
         + 2;
 
-    walls.extend(
-        Position(500 - maximum_y, maximum_y).to_position(Position(500 + maximum_y, maximum_y)),
-    );
+    walls.extend(polyline(&[
+        Position::new(SOURCE.x - maximum_y, maximum_y),
+        Position::new(SOURCE.x + maximum_y, maximum_y),
+    ]));
 
     let mut board = Board {
         walls,
@@ -46,10 +57,10 @@ fn run(input: &str) -> Option<usize> {
     let mut dropped_sand = 0;
 
     while board.running {
-        let sand = Position::default();
-
-        board.fall(sand);
+        board.fall(SOURCE);
         dropped_sand += 1;
+
+        visualizer::record(visualizer, || board.frame());
     }
 
     Some(dropped_sand)
@@ -62,87 +73,13 @@ fn parse_line(input: &str) -> IResult<&str, Vec<Position>> {
 fn parse_position(input: &str) -> IResult<&str, Position> {
     map(
         separated_pair(parse_usize, tag(","), parse_usize),
-        |(x, y)| Position(x, y),
+        |(x, y)| Position::new(x, y),
     )(input)
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
-struct Position(usize, usize);
-
-impl Default for Position {
-    fn default() -> Self {
-        Position(500, 0)
-    }
-}
-
-#[derive(Debug, Clone, PartialEq)]
-enum Direction {
-    Left,
-    Right,
-    Down,
-    Up,
-}
-
-impl Position {
-    fn down(&self) -> Position {
-        Position(self.0, self.1 + 1)
-    }
-
-    fn up(&self) -> Position {
-        Position(self.0, self.1 - 1)
-    }
-
-    fn right(&self) -> Position {
-        Position(self.0 + 1, self.1)
-    }
-
-    fn left(&self) -> Position {
-        Position(self.0 - 1, self.1)
-    }
-
-    fn move_in_direction(&mut self, direction: &Direction) {
-        match direction {
-            Direction::Down => {
-                *self = self.down();
-            }
-            Direction::Up => {
-                *self = self.up();
-            }
-            Direction::Right => {
-                *self = self.right();
-            }
-            Direction::Left => {
-                *self = self.left();
-            }
-        }
-    }
-
-    fn direction_to(&self, Position(target_x, target_y): Position) -> Option<Direction> {
-        match (self.0.cmp(&target_x), self.1.cmp(&target_y)) {
-            (Ordering::Equal, Ordering::Less) => Some(Direction::Down),
-            (Ordering::Equal, Ordering::Greater) => Some(Direction::Up),
-            (Ordering::Less, Ordering::Equal) => Some(Direction::Right),
-            (Ordering::Greater, Ordering::Equal) => Some(Direction::Left),
-            _ => None,
-        }
-    }
-
-    fn to_position(&self, position: Position) -> Vec<Position> {
-        let mut results = vec![];
-        let mut current_position = self.clone();
+type Position = Point2<usize>;
 
-        let direction = current_position.direction_to(position).unwrap();
-
-        while current_position != position {
-            results.push(current_position);
-            current_position.move_in_direction(&direction);
-        }
-
-        results.push(position);
-
-        results
-    }
-}
+const SOURCE: Position = Point2 { x: 500, y: 0 };
 
 type Walls = Vec<Position>;
 type Sand = Vec<Position>;
@@ -162,23 +99,27 @@ impl Board {
         while moving && self.running {
             iter_count += 1;
 
+            let down_left = Direction8::SouthWest.step(sand).expect("inside the cave");
+            let down = Direction8::South.step(sand).expect("inside the cave");
+            let down_right = Direction8::SouthEast.step(sand).expect("inside the cave");
+
             match (
-                self.blocked(sand.down().left()),
-                self.blocked(sand.down()),
-                self.blocked(sand.down().right()),
+                self.blocked(down_left),
+                self.blocked(down),
+                self.blocked(down_right),
             ) {
                 (None, Some(_), _) => {
-                    sand = sand.down().left();
+                    sand = down_left;
                 }
                 (Some(_), Some(_), None) => {
-                    sand = sand.down().right();
+                    sand = down_right;
                 }
                 (_, None, _) => {
-                    sand = sand.down();
+                    sand = down;
                 }
                 (Some(_), Some(_), Some(_)) => {
                     moving = false;
-                    if sand == Position::default() {
+                    if sand == SOURCE {
                         self.running = false;
                     } else {
                         self.sand.push(sand);
@@ -192,6 +133,24 @@ impl Board {
         }
     }
 
+    fn frame(&self) -> Frame {
+        let wall = Cell::new('#', Rgb(120, 120, 120));
+        let sand = Cell::new('o', Rgb(230, 190, 90));
+
+        let points = self
+            .walls
+            .iter()
+            .map(|p| ((p.x as isize, p.y as isize), wall))
+            .chain(
+                self.sand
+                    .iter()
+                    .map(|p| ((p.x as isize, p.y as isize), sand)),
+            )
+            .collect::<Vec<_>>();
+
+        Frame::from_points(&points, Cell::new('.', Rgb::BLACK))
+    }
+
     fn blocked(&self, position: Position) -> Option<Position> {
         self.walls
             .iter()
@@ -214,25 +173,18 @@ mod tests {
     }
 
     #[test]
-    fn direction_to_other_point() {
-        assert_eq!(
-            Position(1, 1).direction_to(Position(3, 1)),
-            Some(Direction::Right)
-        );
-
-        assert_eq!(
-            Position(3, 0).direction_to(Position(3, 1)),
-            Some(Direction::Down)
-        );
-
-        assert_eq!(
-            Position(3, 1).direction_to(Position(3, 0)),
-            Some(Direction::Up)
-        );
+    fn expanding_walls() {
+        let (_, points) = parse_line("498,4 -> 498,6 -> 496,6").unwrap();
 
         assert_eq!(
-            Position(3, 1).direction_to(Position(1, 1)),
-            Some(Direction::Left)
+            polyline(&points),
+            vec![
+                Position::new(498, 4),
+                Position::new(498, 5),
+                Position::new(498, 6),
+                Position::new(497, 6),
+                Position::new(496, 6),
+            ]
         );
     }
 }
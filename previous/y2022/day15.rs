@@ -1,3 +1,5 @@
+use crate::geometry::Point2;
+use crate::interval::IntervalSet;
 use crate::parser::*;
 use nom::{
     bytes::complete::tag,
@@ -10,19 +12,15 @@ use std::ops::Range;
 
 pub fn solve() {
     let input = include_str!("input-day15");
-    for n in 0..4000000 {
-        run(input, n);
-    }
+    println!("Answer: {:?}", run(input, 2000000, 4000000));
 }
 
-#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
-struct Point(isize, isize);
+type Point = Point2<isize>;
 
 trait Locatable {
     fn point(&self) -> Point;
     fn manhattan_distance_to(&self, other: &impl Locatable) -> usize {
-        (self.point().0 - other.point().0).abs() as usize
-            + (self.point().1 - other.point().1).abs() as usize
+        self.point().manhattan_distance(&other.point())
     }
 }
 
@@ -48,114 +46,110 @@ impl Locatable for Sensor {
 }
 
 impl Sensor {
+    fn radius(&self) -> isize {
+        self.manhattan_distance_to(&self.closest_beacon) as isize
+    }
+
+    fn covers(&self, point: Point) -> bool {
+        self.point.manhattan_distance(&point) as isize <= self.radius()
+    }
+
     fn manhattan_distances_unavailable(&self, y: isize) -> Option<Range<isize>> {
-        let y_offset = (self.point.1 - y).abs();
+        let y_offset = (self.point.y - y).abs();
         let width = self.manhattan_distance_to(&self.closest_beacon) as isize - y_offset;
 
         if width < 0 {
             None
         } else {
             Some(Range {
-                start: width * -1 + self.point.0,
-                end: width + 1 + self.point.0,
+                start: width * -1 + self.point.x,
+                end: width + 1 + self.point.x,
             })
         }
     }
 }
 
-#[derive(Clone, Debug, Default)]
-struct Ranges(Vec<Range<isize>>);
-
-impl Ranges {
-    fn add_range(&mut self, range: Range<isize>) {
-        let mut push = false;
-
-        for range_mut in self.0.iter_mut() {
-            if flatten_range(range_mut, &range) {
-                push = true
-            }
-        }
-
-        if push || self.0.is_empty() {
-            self.0.push(range);
-        }
-    }
-
-    fn flatten(&mut self) {
-        let mut res = Ranges::default();
+/// Part one counts the positions on `row` that can't hold a beacon; part two is the tuning
+/// frequency of the one spot within `0..=bounds` on both axes that no sensor covers, or `None`
+/// if there isn't one.
+fn run(input: &str, row: isize, bounds: isize) -> Option<(usize, Option<isize>)> {
+    let (_, sensors) =
+        all_consuming(separated_list1(tag("\n"), parse_sensor))(input.trim()).ok()?;
 
-        for r in sort_ranges(self.0.clone()) {
-            res.add_range(r);
-        }
+    let frequency = distress_beacon(&sensors, bounds).map(|beacon| beacon.x * 4000000 + beacon.y);
 
-        *self = res;
-    }
+    Some((ruled_out(&sensors, row), frequency))
 }
 
-fn sort_ranges(ranges: Vec<Range<isize>>) -> Vec<Range<isize>> {
-    let mut new_ranges = ranges.clone();
-
-    new_ranges.sort_by_key(|r| r.start);
-
-    new_ranges
+fn ruled_out(sensors: &[Sensor], row: isize) -> usize {
+    let covered = sensors
+        .iter()
+        .filter_map(|sensor| sensor.manhattan_distances_unavailable(row))
+        .collect::<IntervalSet<_>>();
+
+    let mut beacons = sensors
+        .iter()
+        .map(|sensor| sensor.closest_beacon.point())
+        .filter(|beacon| beacon.y == row && covered.contains(&beacon.x))
+        .map(|beacon| beacon.x)
+        .collect::<Vec<_>>();
+    beacons.sort();
+    beacons.dedup();
+
+    covered.len() - beacons.len()
 }
 
-fn flatten_range(left: &mut Range<isize>, right: &Range<isize>) -> bool {
-    let mut contained = false;
-    let mut push = false;
-
-    match (left.contains(&right.start), left.contains(&right.end)) {
-        (true, true) => contained = true,
-        (true, false) => {
-            left.end = right.end;
-        }
-        (false, true) => {
-            left.start = right.start;
-        }
-        (false, false) => {
-            if right.contains(&left.start) && right.contains(&left.end) {
-                left.start = right.start;
-                left.end = right.end;
-            } else {
-                push = true;
-            }
-        }
-    }
-
-    if contained {
-        false
-    } else {
-        if push {
-            true
-        } else {
-            false
-        }
-    }
+/// A lone uncovered point has to sit just outside the edge of at least two sensor diamonds, so
+/// it's where a rising edge (`y = x + a`) crosses a falling one (`y = -x + b`). Only if it's
+/// wedged against the edge of the search area instead does this fall back to sweeping rows.
+fn distress_beacon(sensors: &[Sensor], bounds: isize) -> Option<Point> {
+    let in_bounds =
+        |point: &Point| (0..=bounds).contains(&point.x) && (0..=bounds).contains(&point.y);
+
+    let rising = sensors
+        .iter()
+        .flat_map(|sensor| {
+            let offset = sensor.point.y - sensor.point.x;
+            [offset - sensor.radius() - 1, offset + sensor.radius() + 1]
+        })
+        .collect::<Vec<_>>();
+    let falling = sensors
+        .iter()
+        .flat_map(|sensor| {
+            let offset = sensor.point.y + sensor.point.x;
+            [offset - sensor.radius() - 1, offset + sensor.radius() + 1]
+        })
+        .collect::<Vec<_>>();
+
+    rising
+        .iter()
+        .flat_map(|&a| {
+            falling
+                .iter()
+                .filter(move |&&b| (b - a) % 2 == 0)
+                .map(move |&b| Point::new((b - a) / 2, (a + b) / 2))
+        })
+        .find(|&point| in_bounds(&point) && sensors.iter().all(|sensor| !sensor.covers(point)))
+        .or_else(|| sweep(sensors, bounds))
 }
 
-fn run(input: &str, row: isize) -> Option<usize> {
-    let (_, sensors) =
-        all_consuming(separated_list1(tag("\n"), parse_sensor))(input.trim()).ok()?;
-    let mut ranges = vec![];
-    for sensor in &sensors {
-        if let Some(range) = sensor.manhattan_distances_unavailable(row) {
-            ranges.push(range);
-        }
-    }
-
-    let mut r = Ranges::default();
-
-    for range in sort_ranges(ranges) {
-        r.add_range(range);
-    }
-
-    r.flatten();
-
-    if r.0.len() > 1 {
-        println!("{:?} {}", r, row);
-    }
-
-    None
+/// Row by row, the first position in `0..=bounds` left over once every sensor's reach is taken
+/// away.
+fn sweep(sensors: &[Sensor], bounds: isize) -> Option<Point> {
+    let search = IntervalSet::from(0..bounds + 1);
+
+    (0..=bounds).find_map(|y| {
+        let covered = sensors
+            .iter()
+            .filter_map(|sensor| sensor.manhattan_distances_unavailable(y))
+            .collect::<IntervalSet<_>>();
+
+        search
+            .difference(&covered)
+            .iter()
+            .next()
+            .map(|gap| Point::new(gap.start, y))
+    })
 }
 
 fn parse_sensor(input: &str) -> IResult<&str, Sensor> {
@@ -175,15 +169,14 @@ fn parse_point(input: &str) -> IResult<&str, Point> {
     let (input, x) = preceded(tag("x="), terminated(parse_digits, tag(", ")))(input)?;
     let (input, y) = preceded(tag("y="), parse_digits)(input)?;
 
-    Ok((input, Point(x, y)))
+    Ok((input, Point::new(x, y)))
 }
 
+#[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn solve_returns_the_correct_value() {
-        let input = r#"
+    const EXAMPLE: &str = r#"
 Sensor at x=2, y=18: closest beacon is at x=-2, y=15
 Sensor at x=9, y=16: closest beacon is at x=10, y=16
 Sensor at x=13, y=2: closest beacon is at x=15, y=3
@@ -199,27 +192,28 @@ Sensor at x=16, y=7: closest beacon is at x=15, y=3
 Sensor at x=14, y=3: closest beacon is at x=15, y=3
 Sensor at x=20, y=1: closest beacon is at x=15, y=3
 "#;
-        assert_eq!(super::run(input, 10), None)
+
+    #[test]
+    fn solve_returns_the_correct_value() {
+        assert_eq!(super::run(EXAMPLE, 10, 20), Some((26, Some(56000011))));
+        assert_eq!(super::run(EXAMPLE, 10, 5), Some((26, None)));
+    }
+
+    #[test]
+    fn sweeping_agrees_with_the_diamond_edges() {
+        let (_, sensors) =
+            all_consuming(separated_list1(tag("\n"), parse_sensor))(EXAMPLE.trim()).unwrap();
+
+        assert_eq!(distress_beacon(&sensors, 20), Some(Point::new(14, 11)));
+        assert_eq!(sweep(&sensors, 20), Some(Point::new(14, 11)));
     }
 
     #[test]
     fn test_adding_ranges() {
-        let mut ranges = Ranges::default();
-
-        ranges.add_range(Range { start: -5, end: 5 });
-        ranges.add_range(Range { start: -3, end: 2 });
-        ranges.add_range(Range {
-            start: -10,
-            end: 10,
-        });
-        ranges.add_range(Range { start: 2, end: 19 });
-
-        assert_eq!(
-            ranges.0,
-            vec![Range {
-                start: -10,
-                end: 19
-            },]
-        );
+        let ranges = [-5..5, -3..2, -10..10, 2..19]
+            .into_iter()
+            .collect::<IntervalSet<isize>>();
+
+        assert_eq!(ranges, IntervalSet::from(-10..19));
     }
 }
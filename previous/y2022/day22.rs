@@ -6,8 +6,9 @@ use nom::{
     sequence::separated_pair,
     IResult,
 };
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
+use crate::geometry::{direction::Direction4, Point2};
 use crate::parser::parse_digits;
 
 pub fn solve() {
@@ -15,36 +16,12 @@ pub fn solve() {
     println!("Answer: {:?}", run(input));
 }
 
-#[derive(Eq, Hash, PartialEq, Clone, Debug, Copy)]
-struct Position(usize, usize);
+type Position = Point2<usize>;
 
 trait Positioned {
     fn position(&self) -> Position;
 }
 
-#[derive(Debug, Copy, Clone)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
-
-impl Direction {
-    fn rotate(&mut self, rotation: Rotate) {
-        match (&self, rotation) {
-            (Direction::Up, Rotate::Left) => *self = Direction::Left,
-            (Direction::Up, Rotate::Right) => *self = Direction::Right,
-            (Direction::Down, Rotate::Left) => *self = Direction::Right,
-            (Direction::Down, Rotate::Right) => *self = Direction::Left,
-            (Direction::Left, Rotate::Left) => *self = Direction::Down,
-            (Direction::Left, Rotate::Right) => *self = Direction::Up,
-            (Direction::Right, Rotate::Left) => *self = Direction::Up,
-            (Direction::Right, Rotate::Right) => *self = Direction::Down,
-        }
-    }
-}
-
 impl Positioned for Slot {
     fn position(&self) -> Position {
         match self {
@@ -54,10 +31,6 @@ impl Positioned for Slot {
     }
 }
 
-fn times(n: usize) -> impl Iterator {
-    std::iter::repeat(()).take(n)
-}
-
 #[derive(Debug, Clone, Copy)]
 enum Rotate {
     Left,
@@ -70,21 +43,23 @@ enum Movement {
     Rotate(Rotate),
 }
 
-fn run(input: &str) -> Option<usize> {
-    let (_, (mut board, instructions)) = all_consuming(parse)(input).unwrap();
-
-    for movement in instructions {
-        board.apply_movement(movement)
-    }
+/// Part two is `None` if the map doesn't fold into a cube.
+fn run(input: &str) -> Option<(usize, Option<usize>)> {
+    let (_, (board, instructions)) = all_consuming(parse)(input).ok()?;
+    let flat = board.clone().password(&instructions, Wrap::Flat);
+    let cube =
+        Cube::fold(&board.tiles_map).map(|cube| board.password(&instructions, Wrap::Cube(&cube)));
 
-    let direction_score = match board.character.direction {
-        Direction::Up => 3,
-        Direction::Down => 1,
-        Direction::Right => 0,
-        Direction::Left => 2,
-    };
+    Some((flat, cube))
+}
 
-    Some(board.character.position().1 * 1000 + board.character.position().0 * 4 + direction_score)
+/// What happens when the character walks off the edge of the map.
+#[derive(Debug, Clone, Copy)]
+enum Wrap<'a> {
+    /// Reappear on the far side of the same row or column.
+    Flat,
+    /// Carry on over the fold onto the neighbouring face of the cube.
+    Cube(&'a Cube),
 }
 
 #[derive(Debug, Clone)]
@@ -102,7 +77,7 @@ enum Slot {
 #[derive(Debug, Copy, Clone)]
 struct Character {
     position: Position,
-    direction: Direction,
+    direction: Direction4,
 }
 
 impl Positioned for Character {
@@ -111,126 +86,351 @@ impl Positioned for Character {
     }
 }
 
-#[derive(Debug)]
+/// One row or column of the map: where it starts and ends, and where its walls are.
+#[derive(Debug, Clone)]
+struct Line {
+    first: usize,
+    last: usize,
+    walls: BTreeSet<usize>,
+}
+
+impl Line {
+    fn new(at: usize) -> Self {
+        Line {
+            first: at,
+            last: at,
+            walls: BTreeSet::new(),
+        }
+    }
+
+    fn add(&mut self, at: usize, slot: &Slot) {
+        self.first = self.first.min(at);
+        self.last = self.last.max(at);
+
+        if let Slot::Wall(_) = slot {
+            self.walls.insert(at);
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.last - self.first + 1
+    }
+
+    /// How many steps can be taken from `at` without walking into a wall or off the end.
+    fn clear_ahead(&self, at: usize, forward: bool) -> usize {
+        if forward {
+            self.walls
+                .range(at + 1..)
+                .next()
+                .map_or(self.last, |wall| wall - 1)
+                - at
+        } else {
+            at - self
+                .walls
+                .range(..at)
+                .next_back()
+                .map_or(self.first, |wall| wall + 1)
+        }
+    }
+
+    /// Where walking off the end brings you back in.
+    fn wrap(&self, forward: bool) -> usize {
+        if forward {
+            self.first
+        } else {
+            self.last
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 struct Board {
-    tiles: Vec<Vec<Slot>>,
     tiles_map: HashMap<Position, Slot>,
+    rows: HashMap<usize, Line>,
+    columns: HashMap<usize, Line>,
     character: Character,
 }
 
 impl Board {
-    fn apply_movement(&mut self, movement: Movement) {
+    fn password(mut self, movements: &[Movement], wrap: Wrap) -> usize {
+        self.walk(movements, wrap, |_, _| ());
+
+        let direction_score = match self.character.direction {
+            Direction4::East => 0,
+            Direction4::South => 1,
+            Direction4::West => 2,
+            Direction4::North => 3,
+        };
+
+        self.character.position().y * 1000 + self.character.position().x * 4 + direction_score
+    }
+
+    /// Applies every movement in turn, calling `trace` with where the character ended up after
+    /// each one.
+    fn walk(
+        &mut self,
+        movements: &[Movement],
+        wrap: Wrap,
+        mut trace: impl FnMut(&Movement, &Character),
+    ) {
+        for movement in movements {
+            self.apply_movement(movement.clone(), wrap);
+            trace(movement, &self.character);
+        }
+    }
+
+    fn apply_movement(&mut self, movement: Movement, wrap: Wrap) {
         match movement {
-            Movement::Move(count) => {
-                for _ in times(count) {
-                    self.move_character(self.character.direction)
-                }
+            Movement::Move(count) => self.advance(count, wrap),
+            Movement::Rotate(Rotate::Left) => {
+                self.character.direction = self.character.direction.turn_left();
             }
-            Movement::Rotate(rotate) => {
-                self.character.direction.rotate(rotate);
+            Movement::Rotate(Rotate::Right) => {
+                self.character.direction = self.character.direction.turn_right();
             }
         }
     }
 
-    fn move_character(&mut self, direction: Direction) {
-        match self.slot_in_direction(direction) {
-            Slot::Space(new_position) => self.character.position = new_position,
-            Slot::Wall(_) => (),
+    /// Walks `count` steps, jumping straight to the next wall or edge along the current row or
+    /// column rather than stepping one tile at a time.
+    fn advance(&mut self, mut count: usize, wrap: Wrap) {
+        while count > 0 {
+            let direction = self.character.direction;
+            let position = self.character.position();
+            let (line, at, forward) = self.line(direction);
+
+            if line.walls.is_empty() && matches!(wrap, Wrap::Flat) {
+                count %= line.len();
+            }
+
+            let run = line.clear_ahead(at, forward).min(count);
+            let at = if forward { at + run } else { at - run };
+
+            self.character.position = match direction {
+                Direction4::East | Direction4::West => Position::new(at, position.y),
+                Direction4::North | Direction4::South => Position::new(position.x, at),
+            };
+            count -= run;
+
+            if count == 0 || !self.move_character(wrap) {
+                break;
+            }
+
+            count -= 1;
         }
     }
 
-    fn right_most_in_row(&self, row: usize) -> usize {
-        self.tiles
-            .iter()
-            .flatten()
-            .map(|tile| tile.position())
-            .filter(|p| p.1 == row)
-            .map(|p| p.0)
-            .max()
-            .unwrap()
+    /// Takes a single step, returning whether the character moved.
+    fn move_character(&mut self, wrap: Wrap) -> bool {
+        match self.slot_in_direction(self.character.direction, wrap) {
+            (Slot::Space(new_position), direction) => {
+                self.character.position = new_position;
+                self.character.direction = direction;
+                true
+            }
+            (Slot::Wall(_), _) => false,
+        }
     }
 
-    fn left_most_in_row(&self, row: usize) -> usize {
-        self.tiles
-            .iter()
-            .flatten()
-            .map(|tile| tile.position())
-            .filter(|p| p.1 == row)
-            .map(|p| p.0)
-            .min()
-            .unwrap()
-    }
+    /// The row or column the character would walk along in `direction`, their place on it, and
+    /// whether `direction` counts upwards along it.
+    fn line(&self, direction: Direction4) -> (&Line, usize, bool) {
+        let position = self.character.position();
 
-    fn bottom_most_in_column(&self, column: usize) -> usize {
-        self.tiles
-            .iter()
-            .flatten()
-            .map(|tile| tile.position())
-            .filter(|p| p.0 == column)
-            .map(|p| p.1)
-            .max()
-            .unwrap()
+        match direction {
+            Direction4::East => (&self.rows[&position.y], position.x, true),
+            Direction4::West => (&self.rows[&position.y], position.x, false),
+            Direction4::South => (&self.columns[&position.x], position.y, true),
+            Direction4::North => (&self.columns[&position.x], position.y, false),
+        }
     }
 
-    fn top_most_in_column(&self, column: usize) -> usize {
-        self.tiles
-            .iter()
-            .flatten()
-            .map(|tile| tile.position())
-            .filter(|p| p.0 == column)
-            .map(|p| p.1)
-            .min()
-            .unwrap()
-    }
+    /// The slot one step away, along with the direction the character would be facing on
+    /// arrival (which only changes when crossing a fold of the cube).
+    fn slot_in_direction(&self, direction: Direction4, wrap: Wrap) -> (Slot, Direction4) {
+        let position = self.character.position();
 
-    fn slot_in_direction(&self, direction: Direction) -> Slot {
-        let target_position = match direction {
-            Direction::Up => Position(self.character.position().0, self.character.position().1 - 1),
-            Direction::Down => {
-                Position(self.character.position().0, self.character.position().1 + 1)
-            }
-            Direction::Left => {
-                Position(self.character.position().0 - 1, self.character.position().1)
-            }
-            Direction::Right => {
-                Position(self.character.position().0 + 1, self.character.position().1)
+        if let Some(&found_slot) = direction
+            .step(position)
+            .and_then(|target| self.tiles_map.get(&target))
+        {
+            return (found_slot, direction);
+        }
+
+        let (wrapped, direction) = match wrap {
+            Wrap::Flat => {
+                let (line, _, forward) = self.line(direction);
+                let wrapped = match direction {
+                    Direction4::East | Direction4::West => {
+                        Position::new(line.wrap(forward), position.y)
+                    }
+                    Direction4::North | Direction4::South => {
+                        Position::new(position.x, line.wrap(forward))
+                    }
+                };
+
+                (wrapped, direction)
             }
+            Wrap::Cube(cube) => cube.cross(position, direction),
         };
 
-        if let Some(&found_slot) = self.tiles_map.get(&target_position) {
-            found_slot
-        } else {
-            match direction {
-                Direction::Up => *self
-                    .tiles_map
-                    .get(&Position(
-                        self.character.position().0,
-                        self.bottom_most_in_column(self.character.position().0),
-                    ))
-                    .unwrap(),
-                Direction::Down => *self
-                    .tiles_map
-                    .get(&Position(
-                        self.character.position().0,
-                        self.top_most_in_column(self.character.position().0),
-                    ))
-                    .unwrap(),
-                Direction::Left => *self
-                    .tiles_map
-                    .get(&Position(
-                        self.right_most_in_row(self.character.position().1),
-                        self.character.position().1,
-                    ))
-                    .unwrap(),
-                Direction::Right => *self
-                    .tiles_map
-                    .get(&Position(
-                        self.left_most_in_row(self.character.position().1),
-                        self.character.position().1,
-                    ))
-                    .unwrap(),
+        (*self.tiles_map.get(&wrapped).unwrap(), direction)
+    }
+}
+
+/// A unit vector along one of the cube's axes.
+type Axis = [i8; 3];
+
+fn negate(axis: Axis) -> Axis {
+    axis.map(|component| -component)
+}
+
+/// How one face of the net sits on the folded cube: which way its outside faces, and which
+/// directions its own right and down point.
+#[derive(Debug, Clone, Copy)]
+struct Face {
+    normal: Axis,
+    right: Axis,
+    down: Axis,
+}
+
+impl Face {
+    /// The way out of the face across the edge in `direction`.
+    fn outward(&self, direction: Direction4) -> Axis {
+        match direction {
+            Direction4::North => negate(self.down),
+            Direction4::East => self.right,
+            Direction4::South => self.down,
+            Direction4::West => negate(self.right),
+        }
+    }
+
+    /// The direction offsets along the edge in `direction` count in.
+    fn along(&self, direction: Direction4) -> Axis {
+        match direction {
+            Direction4::North | Direction4::South => self.right,
+            Direction4::East | Direction4::West => self.down,
+        }
+    }
+
+    /// The neighbouring face in the net, folded away from this one across `direction`.
+    fn fold(&self, direction: Direction4) -> Face {
+        match direction {
+            Direction4::North => Face {
+                normal: negate(self.down),
+                right: self.right,
+                down: self.normal,
+            },
+            Direction4::East => Face {
+                normal: self.right,
+                right: negate(self.normal),
+                down: self.down,
+            },
+            Direction4::South => Face {
+                normal: self.down,
+                right: self.right,
+                down: negate(self.normal),
+            },
+            Direction4::West => Face {
+                normal: negate(self.right),
+                right: self.normal,
+                down: self.down,
+            },
+        }
+    }
+}
+
+/// The map folded up into a cube. Faces are keyed by their column and row in the net, counting
+/// in whole faces.
+#[derive(Debug)]
+struct Cube {
+    size: usize,
+    faces: HashMap<(usize, usize), Face>,
+}
+
+impl Cube {
+    /// Works out how the six faces of the net fold together, whatever shape the net is.
+    fn fold(tiles: &HashMap<Position, Slot>) -> Option<Cube> {
+        let size = (1..=tiles.len()).find(|size| 6 * size * size >= tiles.len())?;
+
+        if 6 * size * size != tiles.len() {
+            return None;
+        }
+
+        let in_net = |column: usize, row: usize| {
+            tiles.contains_key(&Position::new(column * size + 1, row * size + 1))
+        };
+        let start = tiles
+            .keys()
+            .min_by_key(|position| (position.y, position.x))
+            .map(|position| ((position.x - 1) / size, (position.y - 1) / size))?;
+
+        let mut faces = HashMap::from([(
+            start,
+            Face {
+                normal: [0, 0, 1],
+                right: [1, 0, 0],
+                down: [0, 1, 0],
+            },
+        )]);
+        let mut queue = vec![start];
+
+        while let Some(key) = queue.pop() {
+            let face = faces[&key];
+
+            for direction in Direction4::all() {
+                let Some(next) = direction.step(Point2::new(key.0, key.1)) else {
+                    continue;
+                };
+
+                if in_net(next.x, next.y) && !faces.contains_key(&(next.x, next.y)) {
+                    faces.insert((next.x, next.y), face.fold(direction));
+                    queue.push((next.x, next.y));
+                }
             }
         }
+
+        (faces.len() == 6).then_some(Cube { size, faces })
+    }
+
+    /// Where walking off the edge of the face at `position` lands, and which way the character
+    /// faces afterwards.
+    fn cross(&self, position: Position, direction: Direction4) -> (Position, Direction4) {
+        let size = self.size;
+        let key = ((position.x - 1) / size, (position.y - 1) / size);
+        let local = Point2::new((position.x - 1) % size, (position.y - 1) % size);
+        let face = self.faces[&key];
+
+        let (&(column, row), target) = self
+            .faces
+            .iter()
+            .find(|(_, other)| other.normal == face.outward(direction))
+            .expect("every edge of a cube meets another face");
+        let entering = Direction4::all()
+            .find(|&edge| target.outward(edge) == face.normal)
+            .expect("faces that share an edge point at each other");
+
+        let offset = match direction {
+            Direction4::North | Direction4::South => local.x,
+            Direction4::East | Direction4::West => local.y,
+        };
+        let offset = if face.along(direction) == target.along(entering) {
+            offset
+        } else {
+            size - 1 - offset
+        };
+
+        let (x, y) = match entering {
+            Direction4::North => (offset, 0),
+            Direction4::East => (size - 1, offset),
+            Direction4::South => (offset, size - 1),
+            Direction4::West => (0, offset),
+        };
+
+        (
+            Position::new(column * size + x + 1, row * size + y + 1),
+            entering.opposite(),
+        )
     }
 }
 
@@ -240,38 +440,46 @@ fn parse(input: &str) -> IResult<&str, (Board, Vec<Movement>)> {
 
 fn parse_board(input: &str) -> IResult<&str, Board> {
     map(separated_list1(tag("\n"), parse_row), |rows| {
-        let mut tiles = vec![];
         let mut tiles_map = HashMap::new();
 
         for (y, row) in rows.iter().enumerate() {
-            let mut row_results = vec![];
-
             for (x, col) in row.iter().enumerate() {
-                let position = Position(x + 1, y + 1);
+                let position = Position::new(x + 1, y + 1);
 
                 match col {
                     None => (),
                     Some(ParsedSlot::Space) => {
-                        row_results.push(Slot::Space(position));
                         tiles_map.insert(position, Slot::Space(position));
                     }
                     Some(ParsedSlot::Wall) => {
-                        row_results.push(Slot::Wall(position));
                         tiles_map.insert(position, Slot::Wall(position));
                     }
                 }
             }
+        }
 
-            tiles.push(row_results);
+        let mut rows = HashMap::new();
+        let mut columns = HashMap::new();
+
+        for (position, slot) in &tiles_map {
+            rows.entry(position.y)
+                .or_insert_with(|| Line::new(position.x))
+                .add(position.x, slot);
+            columns
+                .entry(position.x)
+                .or_insert_with(|| Line::new(position.y))
+                .add(position.y, slot);
         }
+
         let character = Character {
-            position: tiles[0][0].position(),
-            direction: Direction::Right,
+            position: Position::new(rows[&1].first, 1),
+            direction: Direction4::East,
         };
 
         Board {
-            tiles,
             tiles_map,
+            rows,
+            columns,
             character,
         }
     })(input)
@@ -297,10 +505,11 @@ fn parse_movement(input: &str) -> IResult<&str, Vec<Movement>> {
     )))(input)
 }
 
+#[cfg(test)]
 mod tests {
-    #[test]
-    fn solve_returns_the_correct_value() {
-        let input = r#"        ...#
+    use super::*;
+
+    const EXAMPLE: &str = r#"        ...#
         .#..
         #...
         ....
@@ -314,6 +523,34 @@ mod tests {
         ......#.
 
 10R5L5R10L4R5L5"#;
-        assert_eq!(super::run(input), Some(6032))
+
+    #[test]
+    fn solve_returns_the_correct_value() {
+        assert_eq!(super::run(EXAMPLE), Some((6032, Some(5031))))
+    }
+
+    #[test]
+    fn maps_that_dont_fold_still_walk_flat() {
+        assert_eq!(super::run("....\n....\n\n5"), Some((1008, None)))
+    }
+
+    #[test]
+    fn tracing_the_walk() {
+        let (_, (mut board, movements)) = parse(EXAMPLE).unwrap();
+        let mut trace = vec![];
+
+        board.walk(&movements, Wrap::Flat, |_, character| {
+            trace.push((character.position, character.direction))
+        });
+
+        assert_eq!(trace.len(), movements.len());
+        assert_eq!(
+            &trace[..3],
+            &[
+                (Position::new(11, 1), Direction4::East),
+                (Position::new(11, 1), Direction4::South),
+                (Position::new(11, 6), Direction4::South),
+            ]
+        );
     }
 }
@@ -11,8 +11,10 @@ use std::ops::Range;
 #[derive(Debug)]
 struct Elf(Range<usize>);
 
+/// Whether the two elves share any section. Overlapping goes both ways, so the order doesn't
+/// matter.
 fn partially_contained(left: &Elf, right: &Elf) -> bool {
-    left.0.contains(&right.0.start) || left.0.contains(&(right.0.end - 1))
+    left.0.start < right.0.end && right.0.start < left.0.end
 }
 
 pub fn solve() {
@@ -23,9 +25,7 @@ pub fn solve() {
 fn run(input: &str) -> Option<usize> {
     let (_, mut elf_pairs) =
         all_consuming(separated_list1(tag("\n"), parse_elf_pair))(input.trim()).ok()?;
-    elf_pairs.retain(|(left, right)| {
-        partially_contained(left, right) || partially_contained(right, left)
-    });
+    elf_pairs.retain(|(left, right)| partially_contained(left, right));
     elf_pairs.len().into()
 }
 
@@ -1,3 +1,5 @@
+use crate::dot::Dot;
+use crate::filesystem::{EntryId, FileSystem};
 use crate::parser::*;
 use nom::{
     branch::alt,
@@ -8,22 +10,24 @@ use nom::{
     sequence::{preceded, separated_pair},
     IResult,
 };
-use slab_tree::{
-    tree::{Tree, TreeBuilder},
-    NodeId,
-};
 
 pub fn solve() {
     let input = include_str!("input-day7");
     println!("Answer: {:?}", run(input));
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
-enum Entry {
-    File(String, usize),
-    Directory(String),
+/// The filesystem as Graphviz, labelled with sizes, with the directory to delete picked out.
+pub fn dot() -> Option<String> {
+    let input = include_str!("input-day7");
+    let fs = replay(input)?;
+    let target = fs.smallest_to_free(DISK_SIZE, NEEDED_SPACE);
+
+    Some(to_dot(&fs, target).to_string())
 }
 
+const DISK_SIZE: usize = 70000000;
+const NEEDED_SPACE: usize = 30000000;
+
 #[derive(Debug, Clone, PartialEq)]
 enum Line {
     File(String, usize),
@@ -34,112 +38,80 @@ enum Line {
     ChangeDirectoryUp,
 }
 
-fn run(input: &str) -> Option<usize> {
-    let (_, lines) = all_consuming(separated_list1(tag("\n"), parse_line))(input.trim()).ok()?;
-
-    let graph = build_fs(lines);
-    let mut scorer: Box<dyn EntryTreeVisitor> = Box::new(Scorer::default());
-    scorer.visit_root(size(&graph.root_id().unwrap(), &graph));
+fn run(input: &str) -> Option<(usize, usize)> {
+    let fs = replay(input)?;
 
-    visit_node(&graph.root_id().unwrap(), &graph, &mut scorer);
+    let small_directories = fs.directories_at_most(100000).map(|id| fs.size(id)).sum();
+    let freed = fs.smallest_to_free(DISK_SIZE, NEEDED_SPACE)?;
 
-    Some(scorer.score())
+    Some((small_directories, fs.size(freed)))
 }
 
-trait EntryTreeVisitor {
-    fn visit_root(&mut self, size: usize);
-    fn visit_file(&mut self, size: usize);
-    fn visit_directory(&mut self, size: usize);
-    fn score(&self) -> usize;
-}
+fn replay(input: &str) -> Option<FileSystem> {
+    let (_, lines) = all_consuming(separated_list1(tag("\n"), parse_line))(input.trim()).ok()?;
 
-struct Scorer {
-    qualifying_scores: Vec<usize>,
-    unused_space: usize,
+    Some(build_fs(lines))
 }
 
-impl Default for Scorer {
-    fn default() -> Self {
-        Self {
-            qualifying_scores: vec![],
-            unused_space: 70000000,
-        }
-    }
+/// Every entry as a node named by its full path, with `highlight` and everything under it drawn
+/// in red.
+fn to_dot(fs: &FileSystem, highlight: Option<EntryId>) -> Dot {
+    let mut dot = Dot::digraph("filesystem");
+    describe(&mut dot, fs, fs.root(), highlight, false);
+    dot
 }
 
-impl EntryTreeVisitor for Scorer {
-    fn visit_root(&mut self, size: usize) {
-        self.unused_space -= size;
-    }
+fn describe(
+    dot: &mut Dot,
+    fs: &FileSystem,
+    id: EntryId,
+    highlight: Option<EntryId>,
+    highlighted: bool,
+) {
+    let highlighted = highlighted || highlight == Some(id);
+    let path = fs.path(id);
 
-    fn visit_file(&mut self, _: usize) {}
-    fn visit_directory(&mut self, size: usize) {
-        if size >= 30000000 - self.unused_space {
-            self.qualifying_scores.push(size);
-        }
-    }
+    dot.node(&path, format!("{} ({})", fs.name(id), fs.size(id)));
 
-    fn score(&self) -> usize {
-        *self.qualifying_scores.iter().min().unwrap()
+    if highlighted {
+        dot.highlight(&path);
     }
-}
 
-fn visit_node(node_id: &NodeId, tree: &Tree<Entry>, visitor: &mut Box<dyn EntryTreeVisitor>) {
-    for child in tree.get(node_id.clone()).expect("tree").children() {
-        match child.data() {
-            Entry::File(_, size) => visitor.visit_file(*size),
-            Entry::Directory(_) => {
-                visit_node(&child.node_id(), tree, visitor);
-                visitor.visit_directory(size(&child.node_id(), tree));
-            }
-        }
-    }
-}
+    for child in fs.children(id) {
+        let child_path = fs.path(child);
+
+        dot.edge(&path, &child_path);
 
-fn size(node_id: &NodeId, tree: &Tree<Entry>) -> usize {
-    let mut total = 0;
-    for child in tree.get(node_id.clone()).expect("tree").children() {
-        match child.data() {
-            Entry::File(_, size) => total += size,
-            Entry::Directory(_) => total += size(&child.node_id(), tree),
+        if highlighted {
+            dot.highlight_edge(&path, &child_path);
         }
-    }
 
-    total
+        describe(dot, fs, child, highlight, highlighted);
+    }
 }
 
-fn build_fs(lines: Vec<Line>) -> Tree<Entry> {
-    let mut fs = TreeBuilder::new()
-        .with_root(Entry::Directory("/".into()))
-        .build();
-
-    let mut current_dir: Option<_> = None;
+fn build_fs(lines: Vec<Line>) -> FileSystem {
+    let mut fs = FileSystem::new();
+    let mut current_dir = fs.root();
 
     for line in lines {
-        match (line, current_dir) {
-            (Line::ChangeDirectoryToRoot, _) => current_dir = fs.root_id(),
-            (Line::ChangeDirectoryUp, Some(dir)) => {
-                current_dir = fs.get(dir).and_then(|v| v.parent().map(|x| x.node_id()));
+        match line {
+            Line::ChangeDirectoryToRoot => current_dir = fs.root(),
+            Line::ChangeDirectoryUp => {
+                current_dir = fs.parent(current_dir).unwrap_or(current_dir);
             }
-            (Line::File(name, size), Some(dir)) => {
-                fs.get_mut(dir)
-                    .expect("found dir")
-                    .append(Entry::File(name.clone(), size));
+            Line::File(name, size) => {
+                fs.add_file(current_dir, &name, size);
             }
-            (Line::Directory(name), Some(dir)) => {
-                fs.get_mut(dir)
-                    .expect("found dir")
-                    .append(Entry::Directory(name.clone()));
+            Line::Directory(name) => {
+                fs.mkdir(current_dir, &name);
             }
-            (Line::ListDirectory, _) => (),
-            (Line::ChangeDirectoryTo(child_dir), Some(dir)) => {
-                for child in fs.get(dir).unwrap().children() {
-                    if child.data() == &Entry::Directory(child_dir.to_string()) {
-                        current_dir = Some(child.node_id())
-                    }
+            Line::ListDirectory => (),
+            Line::ChangeDirectoryTo(child_dir) => {
+                if let Some(dir) = fs.mkdir(current_dir, &child_dir) {
+                    current_dir = dir;
                 }
             }
-            _ => (),
         }
     }
 
@@ -164,7 +136,10 @@ fn parse_line(input: &str) -> IResult<&str, Line> {
     ))(input)
 }
 
+#[cfg(test)]
 mod tests {
+    use super::*;
+
     #[test]
     fn solve_returns_the_correct_value() {
         let input = r#"
@@ -192,6 +167,17 @@ $ ls
 5626152 d.ext
 7214296 k
         "#;
-        assert_eq!(super::run(input.trim()), Some(24933642))
+        assert_eq!(super::run(input.trim()), Some((95437, 24933642)))
+    }
+
+    #[test]
+    fn filesystem_as_dot() {
+        let fs = replay("$ cd /\n$ ls\ndir a\n10 b.txt\n$ cd a\n$ ls\n5 c").unwrap();
+        let text = to_dot(&fs, fs.lookup("/a")).to_string();
+
+        assert!(text.contains("\"/\" [label=\"/ (15)\"];"));
+        assert!(text.contains("\"/a/c\" [label=\"c (5)\", color=red, fontcolor=red];"));
+        assert!(text.contains("\"/a\" -> \"/a/c\" [color=red, penwidth=2];"));
+        assert!(text.contains("\"/\" -> \"/b.txt\";"));
     }
 }
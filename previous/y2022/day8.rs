@@ -1,3 +1,5 @@
+use crate::geometry::{direction::Direction4, Point2};
+use crate::grid::Grid;
 use nom::{
     bytes::complete::tag,
     character::complete::satisfy,
@@ -11,74 +13,41 @@ pub fn solve() {
     println!("Answer: {:?}", run(input));
 }
 
-fn run(input: &str) -> Option<usize> {
+fn run(input: &str) -> Option<(usize, usize)> {
     let (_, matrix) = parse_matrix(input.trim()).ok()?;
-    let pass_1 = matrix
-        .clone()
-        .into_iter()
-        .map(|row| determine_visibility(&row))
-        .collect::<Vec<_>>();
-    let pass_2 = transpose(matrix.clone())
-        .into_iter()
-        .map(|row| determine_visibility(&row))
-        .collect::<Vec<_>>();
-
-    let mut count = 0;
-
-    for (first, second) in pass_1.into_iter().zip(transpose(pass_2)) {
-        count += first
-            .into_iter()
-            .zip(second)
-            .filter(|&(l, r)| l || r)
-            .collect::<Vec<_>>()
-            .len();
-    }
-
-    Some(count)
+    let grid = Grid::new(matrix);
+
+    let visible = grid
+        .points()
+        .filter(|&point| visible_from(&grid, point, &Direction4::ALL))
+        .count();
+    let best_scenic_score = grid
+        .points()
+        .map(|point| scenic_score(&grid, point))
+        .max()?;
+
+    Some((visible, best_scenic_score))
 }
 
-fn determine_visibility(row: &[usize]) -> Vec<bool> {
-    let mut ltr: Vec<bool> = vec![];
-    let mut rtl: Vec<bool> = vec![];
-
-    let mut highest = None;
-
-    for v in row {
-        match (highest, v) {
-            (Some(h), v) if h >= v => {
-                ltr.push(false);
-            }
-            (_, v) => {
-                highest = Some(v);
-                ltr.push(true);
-            }
-        }
-    }
-
-    highest = None;
-
-    for v in row.iter().rev() {
-        match (highest, v) {
-            (Some(h), v) if h >= v => {
-                rtl.insert(0, false);
-            }
-            (_, v) => {
-                highest = Some(v);
-                rtl.insert(0, true);
-            }
-        }
-    }
+fn blocks_view(grid: &Grid<usize>, point: Point2<usize>) -> impl Fn(&usize) -> bool {
+    let height = grid.get(point).copied().unwrap_or_default();
+    move |&tree| tree >= height
+}
 
-    ltr.iter().zip(rtl).map(|(l, r)| *l || r).collect()
+fn visible_from(grid: &Grid<usize>, point: Point2<usize>, directions: &[Direction4]) -> bool {
+    directions.iter().any(|&direction| {
+        grid.cast(point, direction, blocks_view(grid, point))
+            .reached_edge()
+    })
 }
 
-fn transpose<T: Copy>(v: Vec<Vec<T>>) -> Vec<Vec<T>> {
-    assert!(!v.is_empty());
-    let len = v[0].len();
-    (0..len)
-        .into_iter()
-        .map(|i| v.iter().map(|row| row[i]).collect())
-        .collect()
+fn scenic_score(grid: &Grid<usize>, point: Point2<usize>) -> usize {
+    Direction4::all()
+        .map(|direction| {
+            grid.cast(point, direction, blocks_view(grid, point))
+                .distance()
+        })
+        .product()
 }
 
 fn parse_row(input: &str) -> IResult<&str, Vec<usize>> {
@@ -91,7 +60,10 @@ fn parse_matrix(input: &str) -> IResult<&str, Vec<Vec<usize>>> {
     all_consuming(separated_list1(tag("\n"), parse_row))(input)
 }
 
+#[cfg(test)]
 mod tests {
+    use super::*;
+
     #[test]
     fn solve_returns_the_correct_value() {
         let input = r#"
@@ -101,22 +73,36 @@ mod tests {
 33549
 35390
         "#;
-        assert_eq!(super::run(input.trim()), Some(21))
+        assert_eq!(super::run(input.trim()), Some((21, 8)))
     }
 
     #[test]
     fn visibility_in_middle() {
+        let determine_visibility = |row: &[usize]| {
+            let grid = Grid::new(vec![row.to_vec()]);
+
+            (0..row.len())
+                .map(|x| {
+                    visible_from(
+                        &grid,
+                        Point2::new(x, 0),
+                        &[Direction4::West, Direction4::East],
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
         assert_eq!(
-            super::determine_visibility(&[2, 5, 5, 1, 2]),
+            determine_visibility(&[2, 5, 5, 1, 2]),
             vec![true, true, true, false, true]
         );
 
         assert_eq!(
-            super::determine_visibility(&[2, 5, 7, 5, 3, 1, 2]),
+            determine_visibility(&[2, 5, 7, 5, 3, 1, 2]),
             vec![true, true, true, true, true, false, true]
         );
         assert_eq!(
-            super::determine_visibility(&[6, 5, 3, 3, 2]),
+            determine_visibility(&[6, 5, 3, 3, 2]),
             vec![true, true, false, true, true]
         );
     }
@@ -1,21 +1,30 @@
+use crate::geometry::{direction::Direction4, Point2};
 use crate::parser::*;
+use crate::visualizer::{self, Cell, Frame, Rgb, Visualizer};
 use nom::{
-    branch::alt,
     bytes::complete::tag,
-    combinator::{all_consuming, map, value},
+    character::complete::one_of,
+    combinator::{all_consuming, map, map_opt},
     multi::separated_list1,
     sequence::separated_pair,
     IResult,
 };
-use std::{cmp::Ordering, collections::HashSet};
+use std::collections::HashSet;
 
 pub fn solve() {
     let input = include_str!("input-day9");
     println!("Answer: {:?}", run(input));
 }
 
+pub fn animate(visualizer: &mut impl Visualizer) -> std::io::Result<()> {
+    let input = include_str!("input-day9");
+    simulate(input, visualizer);
+
+    visualizer.finish()
+}
+
 trait Moveable {
-    fn move_in_direction(&mut self, direction: &Direction);
+    fn move_in_direction(&mut self, direction: Direction4);
     fn follow(&mut self, knot: &impl Positioned);
 }
 
@@ -23,61 +32,11 @@ trait Positioned {
     fn position(&self) -> Position;
 
     fn adjacent_to_knot(&self, knot: &impl Positioned) -> bool {
-        (knot.position().0 - self.position().0).abs() < 2
-            && (knot.position().1 - self.position().1).abs() < 2
-    }
-
-    fn direction_to_move(&self, knot: &impl Positioned) -> Option<Direction> {
-        if !self.adjacent_to_knot(knot) {
-            match (
-                knot.position().0.cmp(&self.position().0),
-                knot.position().1.cmp(&self.position().1),
-            ) {
-                (Ordering::Equal, Ordering::Greater) => Some(Direction::North),
-                (Ordering::Equal, Ordering::Less) => Some(Direction::South),
-                (Ordering::Greater, Ordering::Equal) => Some(Direction::East),
-                (Ordering::Less, Ordering::Equal) => Some(Direction::West),
-                (Ordering::Greater, Ordering::Greater) => Some(Direction::NorthEast),
-                (Ordering::Greater, Ordering::Less) => Some(Direction::SouthEast),
-                (Ordering::Less, Ordering::Greater) => Some(Direction::NorthWest),
-                (Ordering::Less, Ordering::Less) => Some(Direction::SouthWest),
-                _ => None,
-            }
-        } else {
-            None
-        }
+        self.position().chebyshev_distance(&knot.position()) < 2
     }
 }
 
-#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
-struct Position(isize, isize);
-
-impl Position {
-    fn move_in_direction(&mut self, direction: &Direction) {
-        match direction {
-            Direction::North => self.1 += 1,
-            Direction::South => self.1 -= 1,
-            Direction::East => self.0 += 1,
-            Direction::West => self.0 -= 1,
-            Direction::NorthEast => {
-                self.move_in_direction(&Direction::North);
-                self.move_in_direction(&Direction::East);
-            }
-            Direction::NorthWest => {
-                self.move_in_direction(&Direction::North);
-                self.move_in_direction(&Direction::West);
-            }
-            Direction::SouthEast => {
-                self.move_in_direction(&Direction::South);
-                self.move_in_direction(&Direction::East);
-            }
-            Direction::SouthWest => {
-                self.move_in_direction(&Direction::South);
-                self.move_in_direction(&Direction::West);
-            }
-        }
-    }
-}
+type Position = Point2<isize>;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 struct Knot(Position);
@@ -101,32 +60,32 @@ impl Positioned for Tail {
 }
 
 impl Moveable for Tail {
-    fn move_in_direction(&mut self, direction: &Direction) {
-        self.position.move_in_direction(direction)
+    fn move_in_direction(&mut self, direction: Direction4) {
+        self.position += direction.unit()
     }
 
     fn follow(&mut self, knot: &impl Positioned) {
-        if let Some(direction) = self.direction_to_move(knot) {
-            self.move_in_direction(&direction);
+        if !self.adjacent_to_knot(knot) {
+            self.position = self.position.step_toward(&knot.position());
             self.visited.push(self.position);
         }
     }
 }
 
 impl Moveable for Knot {
-    fn move_in_direction(&mut self, direction: &Direction) {
-        self.0.move_in_direction(direction)
+    fn move_in_direction(&mut self, direction: Direction4) {
+        self.0 += direction.unit()
     }
 
     fn follow(&mut self, knot: &impl Positioned) {
-        if let Some(direction) = self.direction_to_move(knot) {
-            self.move_in_direction(&direction);
+        if !self.adjacent_to_knot(knot) {
+            self.0 = self.0.step_toward(&knot.position());
         }
     }
 }
 
 impl Moveable for Rope {
-    fn move_in_direction(&mut self, direction: &Direction) {
+    fn move_in_direction(&mut self, direction: Direction4) {
         let mut prev: Option<Knot> = None;
 
         for knot in self.body.iter_mut() {
@@ -152,7 +111,7 @@ impl Moveable for Rope {
 
 impl Default for Knot {
     fn default() -> Self {
-        Knot(Position(0, 0))
+        Knot(Position::new(0, 0))
     }
 }
 
@@ -165,25 +124,38 @@ struct Tail {
 impl Default for Tail {
     fn default() -> Self {
         Tail {
-            position: Position(0, 0),
-            visited: vec![Position(0, 0)],
+            position: Position::new(0, 0),
+            visited: vec![Position::new(0, 0)],
         }
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
-enum Direction {
-    North,
-    NorthEast,
-    East,
-    SouthEast,
-    South,
-    SouthWest,
-    West,
-    NorthWest,
+impl Rope {
+    fn frame(&self) -> Frame {
+        let visited = Cell::new('#', Rgb(70, 70, 140));
+        let knot = Cell::new('*', Rgb(230, 230, 230));
+        let head = Cell::new('H', Rgb(230, 80, 60));
+
+        let mut points = self
+            .tail
+            .visited
+            .iter()
+            .map(|p| ((p.x, p.y), visited))
+            .collect::<Vec<_>>();
+
+        points.push(((self.tail.position.x, self.tail.position.y), knot));
+        points.extend(self.body.iter().rev().map(|Knot(p)| ((p.x, p.y), knot)));
+        points.push(((self.body[0].0.x, self.body[0].0.y), head));
+
+        Frame::from_points(&points, Cell::new('.', Rgb::BLACK))
+    }
 }
 
 fn run(input: &str) -> Option<usize> {
+    simulate(input, &mut ())
+}
+
+fn simulate(input: &str, visualizer: &mut impl Visualizer) -> Option<usize> {
     let (_, instructions) =
         all_consuming(separated_list1(tag("\n"), parse_instruction))(input.trim()).ok()?;
 
@@ -191,7 +163,9 @@ fn run(input: &str) -> Option<usize> {
 
     for instruction in &instructions {
         for _ in times(instruction.1) {
-            rope.move_in_direction(&instruction.0);
+            rope.move_in_direction(instruction.0);
+
+            visualizer::record(visualizer, || rope.frame());
         }
     }
 
@@ -209,7 +183,7 @@ fn times(n: usize) -> impl Iterator {
 }
 
 #[derive(Debug)]
-struct Instruction(Direction, usize);
+struct Instruction(Direction4, usize);
 
 fn parse_instruction(input: &str) -> IResult<&str, Instruction> {
     map(
@@ -218,13 +192,8 @@ fn parse_instruction(input: &str) -> IResult<&str, Instruction> {
     )(input)
 }
 
-fn parse_direction(input: &str) -> IResult<&str, Direction> {
-    alt((
-        value(Direction::North, tag("U")),
-        value(Direction::South, tag("D")),
-        value(Direction::East, tag("R")),
-        value(Direction::West, tag("L")),
-    ))(input)
+fn parse_direction(input: &str) -> IResult<&str, Direction4> {
+    map_opt(one_of("UDLR"), Direction4::from_char)(input)
 }
 
 mod tests {
@@ -263,25 +232,25 @@ U 20
     #[test]
     fn adjacency() {
         assert!(Tail {
-            position: Position(0, 0),
+            position: Position::new(0, 0),
             visited: vec![]
         }
         .adjacent_to_knot(&Knot::default()));
 
         assert!(Tail {
-            position: Position(1, 0),
+            position: Position::new(1, 0),
             visited: vec![]
         }
         .adjacent_to_knot(&Knot::default()));
 
         assert!(Tail {
-            position: Position(-1, -1),
+            position: Position::new(-1, -1),
             visited: vec![]
         }
         .adjacent_to_knot(&Knot::default()));
 
         assert!(!Tail {
-            position: Position(2, 0),
+            position: Position::new(2, 0),
             visited: vec![]
         }
         .adjacent_to_knot(&Knot::default()));
@@ -290,46 +259,40 @@ U 20
     #[test]
     fn following() {
         let mut tail = Tail {
-            position: Position(0, 0),
+            position: Position::new(0, 0),
             visited: vec![],
         };
 
-        tail.follow(&Knot(Position(-3, 0)));
-        assert_eq!(tail.position, Position(-1, 0));
+        tail.follow(&Knot(Position::new(-3, 0)));
+        assert_eq!(tail.position, Position::new(-1, 0));
 
-        tail.follow(&Knot(Position(-3, 0)));
-        assert_eq!(tail.position, Position(-2, 0));
+        tail.follow(&Knot(Position::new(-3, 0)));
+        assert_eq!(tail.position, Position::new(-2, 0));
 
-        tail.follow(&Knot(Position(-3, 0)));
-        assert_eq!(tail.position, Position(-2, 0));
+        tail.follow(&Knot(Position::new(-3, 0)));
+        assert_eq!(tail.position, Position::new(-2, 0));
 
-        tail.follow(&Knot(Position(-3, 1)));
-        assert_eq!(tail.position, Position(-2, 0));
+        tail.follow(&Knot(Position::new(-3, 1)));
+        assert_eq!(tail.position, Position::new(-2, 0));
 
-        tail.follow(&Knot(Position(-3, 2)));
-        assert_eq!(tail.position, Position(-3, 1));
+        tail.follow(&Knot(Position::new(-3, 2)));
+        assert_eq!(tail.position, Position::new(-3, 1));
     }
 
     #[test]
     fn direction() {
-        let tail = Tail {
-            position: Position(0, 0),
-            visited: vec![],
-        };
-
-        assert_eq!(
-            tail.direction_to_move(&Knot(Position(-3, 0))).unwrap(),
-            Direction::West
-        );
-
-        assert_eq!(
-            tail.direction_to_move(&Knot(Position(0, 2))).unwrap(),
-            Direction::North
-        );
-
-        assert_eq!(
-            tail.direction_to_move(&Knot(Position(-3, 2))).unwrap(),
-            Direction::NorthWest
-        );
+        for (knot, expected) in [
+            (Position::new(-3, 0), Position::new(-1, 0)),
+            (Position::new(0, 2), Position::new(0, 1)),
+            (Position::new(-3, 2), Position::new(-1, 1)),
+        ] {
+            let mut tail = Tail {
+                position: Position::new(0, 0),
+                visited: vec![],
+            };
+
+            tail.follow(&Knot(knot));
+            assert_eq!(tail.position, expected);
+        }
     }
 }
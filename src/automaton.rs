@@ -0,0 +1,449 @@
+use crate::geometry::{direction::Direction8, Point2};
+use crate::grid::Grid;
+use rayon::prelude::*;
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+/// How a run of generations ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The state stopped changing after this many generations.
+    FixedPoint { generation: usize },
+    /// The state at generation `start + length` repeats the one at `start`.
+    Cycle { start: usize, length: usize },
+}
+
+/// Anything that can be advanced one generation at a time.
+pub trait Automaton {
+    /// What's compared to spot a repeated generation.
+    type State: Clone + PartialEq;
+
+    /// Advances one generation, returning `false` (and leaving the state alone) if nothing would
+    /// change.
+    fn step(&mut self) -> bool;
+
+    /// How many generations have been applied so far.
+    fn generation(&self) -> usize;
+
+    fn state(&self) -> &Self::State;
+
+    fn settle(&mut self) -> Outcome
+    where
+        Self: Sized + Clone,
+    {
+        self.settle_with(|_| ())
+    }
+
+    /// Steps until a fixed point or a repeated state, calling `observe` after every generation.
+    ///
+    /// Repeats are found with Brent's algorithm, so only a couple of states are held at once.
+    /// It can run on a little past the first repeat before noticing it, but the automaton is left
+    /// at generation `start + length`, as if it had stopped right there.
+    fn settle_with(&mut self, mut observe: impl FnMut(&Self)) -> Outcome
+    where
+        Self: Sized + Clone,
+    {
+        let initial = self.clone();
+        // compared against a state saved every power-of-two generations
+        let mut saved = self.state().clone();
+        let (mut power, mut length) = (1, 0);
+
+        loop {
+            if !self.step() {
+                return Outcome::FixedPoint {
+                    generation: self.generation(),
+                };
+            }
+
+            observe(self);
+            length += 1;
+
+            if self.state() == &saved {
+                break;
+            }
+
+            if length == power {
+                saved.clone_from(self.state());
+                power *= 2;
+                length = 0;
+            }
+        }
+
+        // replay from the start with one copy a whole cycle ahead: they first agree where the
+        // cycle begins
+        let mut behind = initial.clone();
+        let mut ahead = initial;
+
+        for _ in 0..length {
+            ahead.step();
+        }
+
+        while behind.state() != ahead.state() {
+            behind.step();
+            ahead.step();
+        }
+
+        *self = ahead;
+
+        Outcome::Cycle {
+            start: behind.generation(),
+            length,
+        }
+    }
+
+    /// A copy of the automaton `generations` generations on. Once its states start repeating,
+    /// whole laps of the cycle are skipped, so `generation` on the copy only counts the ones
+    /// actually run.
+    fn after(&self, generations: usize) -> Self
+    where
+        Self: Sized + Clone,
+    {
+        let mut automaton = self.clone();
+        // Brent's algorithm again: compare against a state saved every power-of-two generations
+        let mut saved = automaton.state().clone();
+        let (mut power, mut length) = (1, 0);
+
+        for done in 1..=generations {
+            if !automaton.step() {
+                break;
+            }
+
+            length += 1;
+
+            if automaton.state() == &saved {
+                // the last `length` generations repeat forever, so only the leftover part of a
+                // lap is still to run
+                for _ in 0..(generations - done) % length {
+                    automaton.step();
+                }
+
+                break;
+            }
+
+            if length == power {
+                saved.clone_from(automaton.state());
+                power *= 2;
+                length = 0;
+            }
+        }
+
+        automaton
+    }
+}
+
+/// Which cells count as a cell's neighbors. Implementations push into `into` so the buffer can
+/// be reused from cell to cell.
+pub trait Neighborhood<T>: Sync {
+    fn neighbors<'a>(&self, grid: &'a Grid<T>, point: Point2<usize>, into: &mut Vec<&'a T>);
+}
+
+/// The eight touching cells.
+#[derive(Debug, Clone, Copy)]
+pub struct Adjacent;
+
+impl<T> Neighborhood<T> for Adjacent {
+    fn neighbors<'a>(&self, grid: &'a Grid<T>, point: Point2<usize>, into: &mut Vec<&'a T>) {
+        into.extend(grid.adjacent(point));
+    }
+}
+
+/// The first cell in each of the eight directions that the predicate matches.
+#[derive(Clone)]
+pub struct LineOfSight<F>(pub F);
+
+impl<T, F: Fn(&T) -> bool + Sync> Neighborhood<T> for LineOfSight<F> {
+    fn neighbors<'a>(&self, grid: &'a Grid<T>, point: Point2<usize>, into: &mut Vec<&'a T>) {
+        into.extend(
+            Direction8::all()
+                .filter_map(|direction| grid.cast(point, direction, &self.0).stopped_at)
+                .filter_map(|point| grid.get(point)),
+        );
+    }
+}
+
+/// An automaton over a rectangular grid. Rows are computed in parallel into a second buffer that
+/// is swapped in afterwards, so no grid is allocated per generation.
+#[derive(Clone)]
+pub struct Dense<T, N, R> {
+    current: Grid<T>,
+    next: Grid<T>,
+    neighborhood: N,
+    rule: R,
+    generation: usize,
+}
+
+impl<T, N, R> Dense<T, N, R>
+where
+    T: Clone + PartialEq + Send + Sync,
+    N: Neighborhood<T>,
+    R: Fn(&T, &[&T]) -> T + Sync,
+{
+    pub fn new(grid: Grid<T>, neighborhood: N, rule: R) -> Self {
+        Dense {
+            next: grid.clone(),
+            current: grid,
+            neighborhood,
+            rule,
+            generation: 0,
+        }
+    }
+
+    pub fn grid(&self) -> &Grid<T> {
+        &self.current
+    }
+
+    pub fn into_grid(self) -> Grid<T> {
+        self.current
+    }
+}
+
+impl<T, N, R> Automaton for Dense<T, N, R>
+where
+    T: Clone + PartialEq + Send + Sync,
+    N: Neighborhood<T>,
+    R: Fn(&T, &[&T]) -> T + Sync,
+{
+    type State = Grid<T>;
+
+    fn step(&mut self) -> bool {
+        let (current, neighborhood, rule) = (&self.current, &self.neighborhood, &self.rule);
+
+        self.next
+            .rows_mut()
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(y, row)| {
+                let mut neighbors = Vec::with_capacity(8);
+
+                for (x, cell) in row.iter_mut().enumerate() {
+                    let point = Point2::new(x, y);
+
+                    neighbors.clear();
+                    neighborhood.neighbors(current, point, &mut neighbors);
+                    *cell = rule(current.get(point).expect("same shape"), &neighbors);
+                }
+            });
+
+        if self.next == self.current {
+            return false;
+        }
+
+        std::mem::swap(&mut self.current, &mut self.next);
+        self.generation += 1;
+        true
+    }
+
+    fn generation(&self) -> usize {
+        self.generation
+    }
+
+    fn state(&self) -> &Grid<T> {
+        &self.current
+    }
+}
+
+/// An automaton over an unbounded space where only some points hold a value (e.g. live cells).
+///
+/// Each generation considers every populated point and its neighbors; `rule` receives the
+/// point's current value (if any) and its populated neighbors' values, and returns the new value
+/// or `None` to leave the point empty.
+#[derive(Clone)]
+pub struct Sparse<P, T, N, R> {
+    cells: HashMap<P, T>,
+    next: HashMap<P, T>,
+    candidates: HashSet<P>,
+    neighborhood: N,
+    rule: R,
+    generation: usize,
+}
+
+impl<P, T, N, R> Sparse<P, T, N, R>
+where
+    P: Clone + Eq + Hash + Send + Sync,
+    T: Clone + PartialEq + Send + Sync,
+    N: Fn(&P) -> Vec<P> + Sync,
+    R: Fn(Option<&T>, &[&T]) -> Option<T> + Sync,
+{
+    pub fn new(cells: HashMap<P, T>, neighborhood: N, rule: R) -> Self {
+        Sparse {
+            cells,
+            next: HashMap::new(),
+            candidates: HashSet::new(),
+            neighborhood,
+            rule,
+            generation: 0,
+        }
+    }
+
+    pub fn cells(&self) -> &HashMap<P, T> {
+        &self.cells
+    }
+}
+
+impl<P, T, N, R> Automaton for Sparse<P, T, N, R>
+where
+    P: Clone + Eq + Hash + Send + Sync,
+    T: Clone + PartialEq + Send + Sync,
+    N: Fn(&P) -> Vec<P> + Sync,
+    R: Fn(Option<&T>, &[&T]) -> Option<T> + Sync,
+{
+    type State = HashMap<P, T>;
+
+    fn step(&mut self) -> bool {
+        self.candidates.clear();
+
+        for point in self.cells.keys() {
+            self.candidates.insert(point.clone());
+            self.candidates.extend((self.neighborhood)(point));
+        }
+
+        let (cells, neighborhood, rule) = (&self.cells, &self.neighborhood, &self.rule);
+        let updates = self
+            .candidates
+            .par_iter()
+            .filter_map(|point| {
+                let neighbors = neighborhood(point)
+                    .iter()
+                    .filter_map(|neighbor| cells.get(neighbor))
+                    .collect::<Vec<_>>();
+
+                rule(cells.get(point), &neighbors).map(|value| (point.clone(), value))
+            })
+            .collect::<Vec<_>>();
+
+        self.next.clear();
+        self.next.extend(updates);
+
+        if self.next == self.cells {
+            return false;
+        }
+
+        std::mem::swap(&mut self.cells, &mut self.next);
+        self.generation += 1;
+        true
+    }
+
+    fn generation(&self) -> usize {
+        self.generation
+    }
+
+    fn state(&self) -> &HashMap<P, T> {
+        &self.cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn life(alive: Option<&()>, neighbors: &[&()]) -> Option<()> {
+        match (alive, neighbors.len()) {
+            (Some(_), 2) | (_, 3) => Some(()),
+            _ => None,
+        }
+    }
+
+    fn around(point: &(isize, isize)) -> Vec<(isize, isize)> {
+        Direction8::all()
+            .map(|direction| direction.unit())
+            .map(|unit| (point.0 + unit.x, point.1 + unit.y))
+            .collect()
+    }
+
+    #[test]
+    fn dense_grids_reach_a_fixed_point() {
+        let grid = Grid::new(vec![vec![0u8, 0, 0], vec![0, 9, 0], vec![0, 0, 0]]);
+        let mut automaton = Dense::new(grid, Adjacent, |&cell: &u8, neighbors: &[&u8]| {
+            cell.max(
+                neighbors
+                    .iter()
+                    .map(|&&n| n.saturating_sub(1))
+                    .max()
+                    .unwrap_or(0),
+            )
+        });
+
+        assert_eq!(automaton.settle(), Outcome::FixedPoint { generation: 1 });
+        assert_eq!(
+            automaton.grid().rows(),
+            &[vec![8, 8, 8], vec![8, 9, 8], vec![8, 8, 8]]
+        );
+    }
+
+    #[test]
+    fn sparse_blinker_cycles() {
+        let blinker = [(0, -1), (0, 0), (0, 1)]
+            .into_iter()
+            .map(|point| (point, ()))
+            .collect();
+        let mut automaton = Sparse::new(blinker, around, life);
+
+        assert_eq!(
+            automaton.settle(),
+            Outcome::Cycle {
+                start: 0,
+                length: 2
+            }
+        );
+        assert_eq!(automaton.generation(), 2);
+    }
+
+    /// Counts 0, 1, 2, 3, 4 and then goes back round from 2.
+    #[derive(Clone)]
+    struct Clock(usize, usize);
+
+    impl Automaton for Clock {
+        type State = usize;
+
+        fn step(&mut self) -> bool {
+            self.0 = if self.0 == 4 { 2 } else { self.0 + 1 };
+            self.1 += 1;
+            true
+        }
+
+        fn generation(&self) -> usize {
+            self.1
+        }
+
+        fn state(&self) -> &usize {
+            &self.0
+        }
+    }
+
+    #[test]
+    fn cycles_are_found_after_a_lead_in() {
+        let mut clock = Clock(0, 0);
+
+        assert_eq!(
+            clock.settle(),
+            Outcome::Cycle {
+                start: 2,
+                length: 3
+            }
+        );
+        assert_eq!((clock.generation(), *clock.state()), (5, 2));
+        assert_eq!(*Clock(0, 0).after(1_000_000).state(), 4);
+    }
+
+    #[test]
+    fn skipping_ahead_matches_stepping() {
+        let mut stepped = Clock(0, 0);
+
+        for generations in 0..50 {
+            assert_eq!(Clock(0, 0).after(generations).state(), stepped.state());
+            stepped.step();
+        }
+    }
+
+    #[test]
+    fn line_of_sight_skips_ignored_cells() {
+        let grid = Grid::new(vec![vec!['#', '.', '#'], vec!['.', '.', '.']]);
+        let mut neighbors = vec![];
+
+        LineOfSight(|&c: &char| c != '.').neighbors(&grid, Point2::new(0, 0), &mut neighbors);
+
+        assert_eq!(neighbors, vec![&'#']);
+    }
+}
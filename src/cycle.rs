@@ -0,0 +1,152 @@
+/// Where a sequence of states starts repeating: the state at step `start + length` is the same as
+/// the one at `start`, and so on forever after.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+impl Cycle {
+    /// The earliest step whose state matches the one at step `n`.
+    pub fn equivalent_step(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.length
+        }
+    }
+
+    /// Extrapolates something that changes by the same amount on every lap of the cycle (a
+    /// running total, the height of a growing tower) out to step `n`. `value_at` only gets asked
+    /// about steps up to `start + length`.
+    pub fn extrapolate(&self, n: usize, value_at: impl Fn(usize) -> isize) -> isize {
+        if n <= self.start + self.length {
+            return value_at(n);
+        }
+
+        let laps = ((n - self.start) / self.length) as isize;
+        let per_lap = value_at(self.start + self.length) - value_at(self.start);
+
+        value_at(self.equivalent_step(n)) + laps * per_lap
+    }
+}
+
+/// Brent's algorithm over `initial, step(initial), step(step(initial)), ...`, treating two
+/// states as the same when their keys match. Only a couple of states are held at once, so the
+/// key can be the whole state or something much smaller.
+///
+/// Returns `None` if the states haven't repeated within about `limit` steps.
+pub fn brent<S: Clone, K: PartialEq>(
+    initial: &S,
+    step: impl Fn(&S) -> S,
+    key: impl Fn(&S) -> K,
+    limit: usize,
+) -> Option<Cycle> {
+    // the hare runs laps of doubling length, and the tortoise jumps to it at the end of each;
+    // once a lap is longer than the cycle, the hare comes round to meet the tortoise
+    let (mut power, mut length) = (1, 1);
+    let mut tortoise = initial.clone();
+    let mut hare = step(initial);
+    let mut steps = 1;
+
+    while key(&tortoise) != key(&hare) {
+        if steps >= limit {
+            return None;
+        }
+
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+
+        hare = step(&hare);
+        length += 1;
+        steps += 1;
+    }
+
+    // with the hare a whole cycle ahead, the two meet right where the cycle begins
+    let mut tortoise = initial.clone();
+    let mut hare = (0..length).fold(initial.clone(), |state, _| step(&state));
+    let mut start = 0;
+
+    while key(&tortoise) != key(&hare) {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    Some(Cycle { start, length })
+}
+
+/// The state after `n` steps. Once the states start repeating, whole laps of the cycle are
+/// skipped rather than run.
+pub fn nth<S: Clone, K: PartialEq>(
+    initial: &S,
+    step: impl Fn(&S) -> S,
+    key: impl Fn(&S) -> K,
+    n: usize,
+) -> S {
+    let steps = match brent(initial, &step, key, n) {
+        Some(cycle) => cycle.equivalent_step(n),
+        None => n,
+    };
+
+    (0..steps).fold(initial.clone(), |state, _| step(&state))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 3, 9, 81, 61, 21, 41, 81, ... so the cycle starts at step 2 with length 4.
+    fn square(x: &u32) -> u32 {
+        x * x % 100
+    }
+
+    #[test]
+    fn finding_a_cycle() {
+        let cycle = brent(&3, square, |&x| x, 1000);
+
+        assert_eq!(
+            cycle,
+            Some(Cycle {
+                start: 2,
+                length: 4
+            })
+        );
+        assert_eq!(brent(&3, square, |&x| x, 4), None);
+        assert_eq!(brent(&0u32, |x| x + 1, |&x| x, 1000), None);
+    }
+
+    #[test]
+    fn skipping_ahead() {
+        let cycle = Cycle {
+            start: 2,
+            length: 4,
+        };
+
+        assert_eq!(cycle.equivalent_step(1), 1);
+        assert_eq!(cycle.equivalent_step(7), 3);
+        assert_eq!(cycle.equivalent_step(1_000_000_000), 4);
+        assert_eq!(nth(&3, square, |&x| x, 1_000_000_000), 21);
+        assert_eq!(
+            nth(&3, square, |&x| x, 1_000_000_000),
+            (0..1_000).fold(3, |x, _| square(&x))
+        );
+    }
+
+    #[test]
+    fn extrapolating_a_running_total() {
+        // the running total of the states above, step by step
+        let totals: [isize; 10] = [0, 3, 12, 93, 154, 175, 216, 297, 358, 379];
+        let cycle = brent(&3, square, |&x| x, 1000).unwrap();
+
+        assert_eq!(cycle.extrapolate(4, |step| totals[step]), 154);
+        assert_eq!(cycle.extrapolate(9, |step| totals[step]), 379);
+        assert_eq!(
+            cycle.extrapolate(1_000_002, |step| totals[step]),
+            12 + 250_000 * 204
+        );
+    }
+}
@@ -0,0 +1,362 @@
+use crate::dot::Dot;
+use crate::search::bfs;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+    hash::Hash,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DagError<N> {
+    /// The edges loop back on themselves, so there is no order to visit the nodes in. Holds one
+    /// such loop, starting and ending at the same node.
+    Cycle(Vec<N>),
+    /// A query named a node the graph has never seen.
+    UnknownNode(N),
+}
+
+impl<N: fmt::Display> fmt::Display for DagError<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DagError::Cycle(nodes) => {
+                write!(f, "cycle through ")?;
+
+                for (idx, node) in nodes.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, " -> ")?;
+                    }
+                    write!(f, "{}", node)?;
+                }
+
+                Ok(())
+            }
+            DagError::UnknownNode(node) => write!(f, "no node named {}", node),
+        }
+    }
+}
+
+impl<N: fmt::Debug + fmt::Display> std::error::Error for DagError<N> {}
+
+/// A directed graph with weighted edges (e.g. "a shiny gold bag holds 2 dark red bags"), meant to
+/// be acyclic. Nothing stops a cycle being added; the queries that need an order report it.
+#[derive(Debug, Clone)]
+pub struct Dag<N> {
+    nodes: Vec<N>,
+    index: HashMap<N, usize>,
+    children: Vec<Vec<(usize, usize)>>,
+    parents: Vec<Vec<usize>>,
+}
+
+impl<N: Clone + Eq + Hash> Dag<N> {
+    pub fn new() -> Self {
+        Dag {
+            nodes: vec![],
+            index: HashMap::new(),
+            children: vec![],
+            parents: vec![],
+        }
+    }
+
+    /// Adds a node with no edges, or does nothing if it's already there.
+    pub fn add_node(&mut self, node: N) {
+        self.id(node);
+    }
+
+    /// Adds an edge from `from` to `to`, adding either node if needed.
+    pub fn add_edge(&mut self, from: N, to: N, weight: usize) {
+        let (from, to) = (self.id(from), self.id(to));
+
+        self.children[from].push((to, weight));
+        self.parents[to].push(from);
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn nodes(&self) -> &[N] {
+        &self.nodes
+    }
+
+    /// The nodes `node` has edges to, with their weights.
+    pub fn children(&self, node: &N) -> Result<impl Iterator<Item = (&N, usize)>, DagError<N>> {
+        let id = self.lookup(node)?;
+
+        Ok(self.children[id]
+            .iter()
+            .map(|&(child, weight)| (&self.nodes[child], weight)))
+    }
+
+    /// Every node, each one ahead of everything it has edges to.
+    pub fn topological_order(&self) -> Result<Vec<&N>, DagError<N>> {
+        Ok(self
+            .order()?
+            .into_iter()
+            .map(|id| &self.nodes[id])
+            .collect())
+    }
+
+    /// For every node, the sum over each path below it of the product of the weights along the
+    /// path: how many bags are inside a bag, all the way down. Each node is worked out once,
+    /// after everything below it.
+    pub fn weighted_descendants(&self) -> Result<HashMap<&N, usize>, DagError<N>> {
+        let mut totals = vec![0; self.nodes.len()];
+
+        for id in self.order()?.into_iter().rev() {
+            totals[id] = self.children[id]
+                .iter()
+                .map(|&(child, weight)| weight * (1 + totals[child]))
+                .sum();
+        }
+
+        Ok(self.nodes.iter().zip(totals).collect())
+    }
+
+    /// The weighted descendant total for one node.
+    pub fn weighted_descendants_of(&self, node: &N) -> Result<usize, DagError<N>> {
+        let id = self.lookup(node)?;
+        let totals = self.weighted_descendants()?;
+
+        Ok(totals[&self.nodes[id]])
+    }
+
+    /// Every node with a path to `node`, not counting `node` itself.
+    pub fn ancestors(&self, node: &N) -> Result<HashSet<&N>, DagError<N>> {
+        let id = self.lookup(node)?;
+        let explored = bfs([id], |&id| self.parents[id].iter().copied(), |_| false);
+
+        Ok(explored
+            .visited()
+            .iter()
+            .filter(|&&other| other != id)
+            .map(|&other| &self.nodes[other])
+            .collect())
+    }
+
+    /// Every node reachable from `node`, not counting `node` itself.
+    pub fn descendants(&self, node: &N) -> Result<HashSet<&N>, DagError<N>> {
+        let id = self.lookup(node)?;
+        let explored = bfs(
+            [id],
+            |&id| self.children[id].iter().map(|&(child, _)| child),
+            |_| false,
+        );
+
+        Ok(explored
+            .visited()
+            .iter()
+            .filter(|&&other| other != id)
+            .map(|&other| &self.nodes[other])
+            .collect())
+    }
+
+    /// The graph as Graphviz, with edges labelled by weight. If `highlight` is given, it and
+    /// everything below it are drawn in red.
+    pub fn to_dot(&self, name: &str, highlight: Option<&N>) -> Result<Dot, DagError<N>>
+    where
+        N: fmt::Display,
+    {
+        let mut dot = Dot::digraph(name);
+
+        for node in &self.nodes {
+            dot.node(node, node);
+        }
+
+        for (id, children) in self.children.iter().enumerate() {
+            for &(child, weight) in children {
+                dot.weighted_edge(&self.nodes[id], &self.nodes[child], weight);
+            }
+        }
+
+        if let Some(root) = highlight {
+            let mut subtree = self.descendants(root)?;
+            subtree.insert(root);
+
+            for &node in &subtree {
+                dot.highlight(node);
+
+                for (child, _) in self.children(node)? {
+                    dot.highlight_edge(node, child);
+                }
+            }
+        }
+
+        Ok(dot)
+    }
+
+    fn id(&mut self, node: N) -> usize {
+        if let Some(&id) = self.index.get(&node) {
+            return id;
+        }
+
+        let id = self.nodes.len();
+        self.index.insert(node.clone(), id);
+        self.nodes.push(node);
+        self.children.push(vec![]);
+        self.parents.push(vec![]);
+        id
+    }
+
+    fn lookup(&self, node: &N) -> Result<usize, DagError<N>> {
+        self.index
+            .get(node)
+            .copied()
+            .ok_or_else(|| DagError::UnknownNode(node.clone()))
+    }
+
+    /// Kahn's algorithm. Anything left over once no node is free of incoming edges sits on or
+    /// below a cycle.
+    fn order(&self) -> Result<Vec<usize>, DagError<N>> {
+        let mut incoming = self.parents.iter().map(Vec::len).collect::<Vec<_>>();
+        let mut ready = (0..self.nodes.len())
+            .filter(|&id| incoming[id] == 0)
+            .collect::<VecDeque<_>>();
+        let mut order = Vec::with_capacity(self.nodes.len());
+
+        while let Some(id) = ready.pop_front() {
+            order.push(id);
+
+            for &(child, _) in &self.children[id] {
+                incoming[child] -= 1;
+
+                if incoming[child] == 0 {
+                    ready.push_back(child);
+                }
+            }
+        }
+
+        if order.len() == self.nodes.len() {
+            return Ok(order);
+        }
+
+        // every leftover node still has a leftover parent, so walking parents must loop
+        let mut id = (0..self.nodes.len())
+            .find(|&id| incoming[id] > 0)
+            .expect("some node was left over");
+        let mut seen = vec![id];
+
+        loop {
+            id = *self.parents[id]
+                .iter()
+                .find(|&&parent| incoming[parent] > 0)
+                .expect("leftover nodes have a leftover parent");
+
+            if let Some(start) = seen.iter().position(|&other| other == id) {
+                // `seen` runs child to parent, so reverse it to follow the edges forwards
+                let mut cycle = seen[start..]
+                    .iter()
+                    .rev()
+                    .map(|&id| self.nodes[id].clone())
+                    .collect::<Vec<_>>();
+                cycle.push(cycle[0].clone());
+
+                return Err(DagError::Cycle(cycle));
+            }
+
+            seen.push(id);
+        }
+    }
+}
+
+impl<N: Clone + Eq + Hash> Default for Dag<N> {
+    fn default() -> Self {
+        Dag::new()
+    }
+}
+
+impl<N: Clone + Eq + Hash> FromIterator<(N, N, usize)> for Dag<N> {
+    fn from_iter<I: IntoIterator<Item = (N, N, usize)>>(edges: I) -> Self {
+        let mut dag = Dag::new();
+
+        for (from, to, weight) in edges {
+            dag.add_edge(from, to, weight);
+        }
+
+        dag
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bags() -> Dag<&'static str> {
+        [
+            ("gold", "olive", 1),
+            ("gold", "plum", 2),
+            ("olive", "blue", 3),
+            ("olive", "black", 4),
+            ("plum", "blue", 5),
+            ("plum", "black", 6),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn topological_order_puts_parents_first() {
+        let dag = bags();
+        let order = dag.topological_order().unwrap();
+        let position = |node| order.iter().position(|&&other| other == node).unwrap();
+
+        assert_eq!(order.len(), 5);
+        assert!(position("gold") < position("olive"));
+        assert!(position("plum") < position("black"));
+    }
+
+    #[test]
+    fn weighted_descendants_multiply_down_each_path() {
+        let dag = bags();
+
+        assert_eq!(dag.weighted_descendants_of(&"olive"), Ok(7));
+        assert_eq!(dag.weighted_descendants_of(&"gold"), Ok(32));
+        assert_eq!(
+            dag.weighted_descendants_of(&"red"),
+            Err(DagError::UnknownNode("red"))
+        );
+    }
+
+    #[test]
+    fn ancestors_follow_edges_backwards() {
+        let dag = bags();
+
+        assert_eq!(
+            dag.ancestors(&"blue").unwrap(),
+            HashSet::from([&"gold", &"olive", &"plum"])
+        );
+        assert!(dag.ancestors(&"gold").unwrap().is_empty());
+    }
+
+    #[test]
+    fn dot_highlights_a_subtree() {
+        let text = bags().to_dot("bags", Some(&"plum")).unwrap().to_string();
+
+        assert!(text.contains("\"plum\" -> \"blue\" [label=\"5\", color=red, penwidth=2];"));
+        assert!(text.contains("\"gold\" -> \"plum\" [label=\"2\"];"));
+        assert!(text.contains("\"black\" [label=\"black\", color=red, fontcolor=red];"));
+        assert!(text.contains("\"olive\" [label=\"olive\"];"));
+    }
+
+    #[test]
+    fn cycles_are_reported() {
+        let mut dag = bags();
+        dag.add_edge("black", "gold", 1);
+
+        let Err(DagError::Cycle(cycle)) = dag.topological_order() else {
+            panic!("expected a cycle");
+        };
+
+        assert_eq!(cycle.first(), cycle.last());
+        assert!(cycle.windows(2).all(|pair| dag
+            .children(&pair[0])
+            .unwrap()
+            .any(|(&to, _)| to == pair[1])));
+        assert!(DagError::Cycle(cycle)
+            .to_string()
+            .starts_with("cycle through "));
+    }
+}
@@ -0,0 +1,211 @@
+use std::{collections::HashSet, fmt};
+
+/// A Graphviz DOT document, built up node by node and rendered with `to_string()`.
+///
+/// Nodes are identified by whatever `Display`s them and drawn with a separate label. Edges may
+/// name nodes that were never added, and Graphviz will draw them unlabelled.
+#[derive(Debug, Clone)]
+pub struct Dot {
+    name: String,
+    directed: bool,
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+    highlighted: HashSet<String>,
+    highlighted_edges: HashSet<(String, String)>,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    id: String,
+    label: String,
+}
+
+#[derive(Debug, Clone)]
+struct Edge {
+    from: String,
+    to: String,
+    weight: Option<String>,
+}
+
+impl Dot {
+    pub fn digraph(name: impl fmt::Display) -> Self {
+        Dot::new(name, true)
+    }
+
+    pub fn graph(name: impl fmt::Display) -> Self {
+        Dot::new(name, false)
+    }
+
+    fn new(name: impl fmt::Display, directed: bool) -> Self {
+        Dot {
+            name: name.to_string(),
+            directed,
+            nodes: vec![],
+            edges: vec![],
+            highlighted: HashSet::new(),
+            highlighted_edges: HashSet::new(),
+        }
+    }
+
+    pub fn node(&mut self, id: impl fmt::Display, label: impl fmt::Display) -> &mut Self {
+        self.nodes.push(Node {
+            id: id.to_string(),
+            label: label.to_string(),
+        });
+        self
+    }
+
+    pub fn edge(&mut self, from: impl fmt::Display, to: impl fmt::Display) -> &mut Self {
+        self.edges.push(Edge {
+            from: from.to_string(),
+            to: to.to_string(),
+            weight: None,
+        });
+        self
+    }
+
+    /// An edge labelled with its weight.
+    pub fn weighted_edge(
+        &mut self,
+        from: impl fmt::Display,
+        to: impl fmt::Display,
+        weight: impl fmt::Display,
+    ) -> &mut Self {
+        self.edges.push(Edge {
+            from: from.to_string(),
+            to: to.to_string(),
+            weight: Some(weight.to_string()),
+        });
+        self
+    }
+
+    /// Draws a node in red.
+    pub fn highlight(&mut self, id: impl fmt::Display) -> &mut Self {
+        self.highlighted.insert(id.to_string());
+        self
+    }
+
+    /// Draws the edge between two nodes in red. Direction doesn't matter for undirected graphs.
+    pub fn highlight_edge(&mut self, from: impl fmt::Display, to: impl fmt::Display) -> &mut Self {
+        self.highlighted_edges
+            .insert((from.to_string(), to.to_string()));
+        self
+    }
+
+    /// Highlights every node on the path and the edges joining each one to the next.
+    pub fn highlight_path<T: fmt::Display>(
+        &mut self,
+        path: impl IntoIterator<Item = T>,
+    ) -> &mut Self {
+        let path = path
+            .into_iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>();
+
+        for pair in path.windows(2) {
+            self.highlight_edge(&pair[0], &pair[1]);
+        }
+
+        self.highlighted.extend(path);
+        self
+    }
+
+    fn edge_highlighted(&self, edge: &Edge) -> bool {
+        let forward = (edge.from.clone(), edge.to.clone());
+        let backward = (edge.to.clone(), edge.from.clone());
+
+        self.highlighted_edges.contains(&forward)
+            || (!self.directed && self.highlighted_edges.contains(&backward))
+    }
+}
+
+impl fmt::Display for Dot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (keyword, arrow) = if self.directed {
+            ("digraph", "->")
+        } else {
+            ("graph", "--")
+        };
+
+        writeln!(f, "{} {} {{", keyword, quote(&self.name))?;
+
+        for node in &self.nodes {
+            write!(f, "    {} [label={}", quote(&node.id), quote(&node.label))?;
+
+            if self.highlighted.contains(&node.id) {
+                write!(f, ", color=red, fontcolor=red")?;
+            }
+
+            writeln!(f, "];")?;
+        }
+
+        for edge in &self.edges {
+            write!(f, "    {} {} {}", quote(&edge.from), arrow, quote(&edge.to))?;
+
+            let mut attributes = vec![];
+
+            if let Some(weight) = &edge.weight {
+                attributes.push(format!("label={}", quote(weight)));
+            }
+
+            if self.edge_highlighted(edge) {
+                attributes.push("color=red, penwidth=2".to_string());
+            }
+
+            if !attributes.is_empty() {
+                write!(f, " [{}]", attributes.join(", "))?;
+            }
+
+            writeln!(f, ";")?;
+        }
+
+        writeln!(f, "}}")
+    }
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_nodes_and_weighted_edges() {
+        let mut dot = Dot::digraph("bags");
+        dot.node("gold", "shiny gold")
+            .node("red", "dark red")
+            .weighted_edge("gold", "red", 2);
+
+        assert_eq!(
+            dot.to_string(),
+            "digraph \"bags\" {\n    \"gold\" [label=\"shiny gold\"];\n    \"red\" [label=\"dark red\"];\n    \"gold\" -> \"red\" [label=\"2\"];\n}\n"
+        );
+    }
+
+    #[test]
+    fn highlighting_a_path() {
+        let mut dot = Dot::graph("path");
+        dot.node(1, 'a')
+            .node(2, 'b')
+            .node(3, 'c')
+            .edge(1, 2)
+            .edge(3, 2)
+            .edge(1, 3)
+            .highlight_path([1, 2, 3]);
+        let text = dot.to_string();
+
+        assert!(text.contains("\"1\" [label=\"a\", color=red, fontcolor=red];"));
+        assert!(text.contains("\"3\" -- \"2\" [color=red, penwidth=2];"));
+        assert!(text.contains("\"1\" -- \"3\";"));
+    }
+
+    #[test]
+    fn quotes_are_escaped() {
+        let mut dot = Dot::digraph("q");
+        dot.node("say \"hi\"", "a\\b");
+
+        assert!(dot.to_string().contains(r#""say \"hi\"" [label="a\\b"];"#));
+    }
+}
@@ -0,0 +1,285 @@
+use std::{collections::BTreeMap, fmt};
+
+/// A handle to a file or directory in a [`FileSystem`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EntryId(usize);
+
+#[derive(Debug, Clone)]
+enum Kind {
+    File,
+    /// Children sorted by name, so listings come out in a stable order.
+    Directory(BTreeMap<String, EntryId>),
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    name: String,
+    parent: Option<EntryId>,
+    /// For a directory, the total of everything beneath it, kept up to date as files are added.
+    size: usize,
+    kind: Kind,
+}
+
+/// An in-memory tree of directories and sized files, rooted at `/`.
+#[derive(Debug, Clone)]
+pub struct FileSystem {
+    entries: Vec<Entry>,
+}
+
+impl FileSystem {
+    pub fn new() -> Self {
+        FileSystem {
+            entries: vec![Entry {
+                name: "/".into(),
+                parent: None,
+                size: 0,
+                kind: Kind::Directory(BTreeMap::new()),
+            }],
+        }
+    }
+
+    pub fn root(&self) -> EntryId {
+        EntryId(0)
+    }
+
+    /// Creates a directory inside `parent`, or returns the existing one of that name. Returns
+    /// `None` if `parent` isn't a directory or the name is taken by a file.
+    pub fn mkdir(&mut self, parent: EntryId, name: &str) -> Option<EntryId> {
+        match self.child(parent, name) {
+            Some(existing) => self.is_directory(existing).then_some(existing),
+            None => self.insert(parent, name, 0, Kind::Directory(BTreeMap::new())),
+        }
+    }
+
+    /// Creates a file inside `parent` and adds its size to every directory above it. Listing the
+    /// same file twice leaves it (and the sizes) as they were.
+    pub fn add_file(&mut self, parent: EntryId, name: &str, size: usize) -> Option<EntryId> {
+        if let Some(existing) = self.child(parent, name) {
+            return (!self.is_directory(existing)).then_some(existing);
+        }
+
+        let id = self.insert(parent, name, size, Kind::File)?;
+        let mut ancestor = Some(parent);
+
+        while let Some(EntryId(idx)) = ancestor {
+            self.entries[idx].size += size;
+            ancestor = self.entries[idx].parent;
+        }
+
+        Some(id)
+    }
+
+    fn insert(&mut self, parent: EntryId, name: &str, size: usize, kind: Kind) -> Option<EntryId> {
+        let id = EntryId(self.entries.len());
+
+        match &mut self.entries.get_mut(parent.0)?.kind {
+            Kind::Directory(children) => children.insert(name.into(), id),
+            Kind::File => return None,
+        };
+
+        self.entries.push(Entry {
+            name: name.into(),
+            parent: Some(parent),
+            size,
+            kind,
+        });
+
+        Some(id)
+    }
+
+    pub fn parent(&self, id: EntryId) -> Option<EntryId> {
+        self.entries[id.0].parent
+    }
+
+    pub fn child(&self, id: EntryId, name: &str) -> Option<EntryId> {
+        match &self.entries[id.0].kind {
+            Kind::Directory(children) => children.get(name).copied(),
+            Kind::File => None,
+        }
+    }
+
+    /// Follows an absolute path like `/a/e`. `..` steps up a level.
+    pub fn lookup(&self, path: &str) -> Option<EntryId> {
+        path.split('/')
+            .filter(|part| !part.is_empty())
+            .try_fold(self.root(), |id, part| match part {
+                "." => Some(id),
+                ".." => self.parent(id),
+                name => self.child(id, name),
+            })
+    }
+
+    pub fn name(&self, id: EntryId) -> &str {
+        &self.entries[id.0].name
+    }
+
+    /// The size of a file, or the total size of everything in a directory.
+    pub fn size(&self, id: EntryId) -> usize {
+        self.entries[id.0].size
+    }
+
+    pub fn is_directory(&self, id: EntryId) -> bool {
+        matches!(self.entries[id.0].kind, Kind::Directory(_))
+    }
+
+    /// The entries directly inside `id`, sorted by name.
+    pub fn children(&self, id: EntryId) -> impl Iterator<Item = EntryId> + '_ {
+        let children = match &self.entries[id.0].kind {
+            Kind::Directory(children) => Some(children.values().copied()),
+            Kind::File => None,
+        };
+
+        children.into_iter().flatten()
+    }
+
+    pub fn path(&self, id: EntryId) -> String {
+        let mut names = vec![];
+        let mut current = Some(id);
+
+        while let Some(id) = current.filter(|&id| id != self.root()) {
+            names.push(self.name(id));
+            current = self.parent(id);
+        }
+
+        names.reverse();
+        format!("/{}", names.join("/"))
+    }
+
+    /// Every directory, including the root.
+    pub fn directories(&self) -> impl Iterator<Item = EntryId> + '_ {
+        (0..self.entries.len())
+            .map(EntryId)
+            .filter(|&id| self.is_directory(id))
+    }
+
+    pub fn directories_at_most(&self, limit: usize) -> impl Iterator<Item = EntryId> + '_ {
+        self.directories().filter(move |&id| self.size(id) <= limit)
+    }
+
+    /// The smallest directory that, once deleted, would leave at least `needed` free on a disk
+    /// holding `capacity`.
+    pub fn smallest_to_free(&self, capacity: usize, needed: usize) -> Option<EntryId> {
+        let free = capacity.saturating_sub(self.size(self.root()));
+        let missing = needed.saturating_sub(free);
+
+        self.directories()
+            .filter(|&id| self.size(id) >= missing)
+            .min_by_key(|&id| self.size(id))
+    }
+
+    fn write_entry(&self, f: &mut fmt::Formatter<'_>, id: EntryId, depth: usize) -> fmt::Result {
+        let kind = if self.is_directory(id) { "dir" } else { "file" };
+
+        writeln!(
+            f,
+            "{:indent$}- {} ({}, size={})",
+            "",
+            self.name(id),
+            kind,
+            self.size(id),
+            indent = depth * 2
+        )?;
+
+        for child in self.children(id) {
+            self.write_entry(f, child, depth + 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for FileSystem {
+    fn default() -> Self {
+        FileSystem::new()
+    }
+}
+
+/// Prints the whole tree, `tree`-style, one entry per line with its size.
+impl fmt::Display for FileSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_entry(f, self.root(), 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> FileSystem {
+        let mut fs = FileSystem::new();
+        let root = fs.root();
+        let a = fs.mkdir(root, "a").unwrap();
+        let e = fs.mkdir(a, "e").unwrap();
+        let d = fs.mkdir(root, "d").unwrap();
+
+        fs.add_file(root, "b.txt", 14848514);
+        fs.add_file(root, "c.dat", 8504156);
+        fs.add_file(a, "f", 29116);
+        fs.add_file(a, "g", 2557);
+        fs.add_file(a, "h.lst", 62596);
+        fs.add_file(e, "i", 584);
+
+        for (name, size) in [
+            ("j", 4060174),
+            ("d.log", 8033020),
+            ("d.ext", 5626152),
+            ("k", 7214296),
+        ] {
+            fs.add_file(d, name, size);
+        }
+
+        fs
+    }
+
+    #[test]
+    fn sizes_roll_up_to_every_parent() {
+        let mut fs = example();
+
+        assert_eq!(fs.size(fs.lookup("/a/e").unwrap()), 584);
+        assert_eq!(fs.size(fs.lookup("/a").unwrap()), 94853);
+        assert_eq!(fs.size(fs.root()), 48381165);
+
+        let a = fs.lookup("/a").unwrap();
+        fs.add_file(a, "f", 29116);
+        assert_eq!(fs.size(fs.root()), 48381165);
+    }
+
+    #[test]
+    fn paths_round_trip() {
+        let fs = example();
+        let e = fs.lookup("/a/e").unwrap();
+
+        assert_eq!(fs.path(e), "/a/e");
+        assert_eq!(fs.path(fs.root()), "/");
+        assert_eq!(fs.lookup("/a/e/../../d"), fs.lookup("/d"));
+        assert_eq!(fs.lookup("/a/missing"), None);
+        assert_eq!(fs.lookup("/b.txt/x"), None);
+    }
+
+    #[test]
+    fn du_style_queries() {
+        let fs = example();
+        let small = fs
+            .directories_at_most(100_000)
+            .map(|id| fs.size(id))
+            .sum::<usize>();
+        let freed = fs.smallest_to_free(70_000_000, 30_000_000).unwrap();
+
+        assert_eq!(small, 95437);
+        assert_eq!(fs.path(freed), "/d");
+        assert_eq!(fs.size(freed), 24933642);
+    }
+
+    #[test]
+    fn printing_the_tree() {
+        let fs = example();
+        let printed = fs.to_string();
+        let lines = printed.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[0], "- / (dir, size=48381165)");
+        assert_eq!(lines[1], "  - a (dir, size=94853)");
+        assert_eq!(lines[2], "    - e (dir, size=584)");
+        assert_eq!(lines[3], "      - i (file, size=584)");
+        assert_eq!(lines.len(), 14);
+    }
+}
@@ -0,0 +1,276 @@
+use std::ops::{Add, AddAssign, Mul, Sub, SubAssign};
+
+pub mod direction;
+pub mod line;
+
+/// The integer types a point can be built from.
+pub trait Coordinate:
+    Copy + Ord + Default + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn distance(self, other: Self) -> usize;
+
+    /// Adds a signed offset, returning `None` if the result doesn't fit (e.g. stepping left of
+    /// column zero on a `usize` grid).
+    fn offset(self, delta: isize) -> Option<Self>;
+
+    /// Moves one unit toward `other`, or stays put if already there.
+    fn step_toward(self, other: Self) -> Self {
+        match self.cmp(&other) {
+            std::cmp::Ordering::Less => self + Self::ONE,
+            std::cmp::Ordering::Greater => self - Self::ONE,
+            std::cmp::Ordering::Equal => self,
+        }
+    }
+}
+
+macro_rules! coordinate {
+    ($($t:ty),*) => {
+        $(
+            impl Coordinate for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+
+                fn distance(self, other: Self) -> usize {
+                    self.abs_diff(other) as usize
+                }
+
+                fn offset(self, delta: isize) -> Option<Self> {
+                    Self::try_from(self as i128 + delta as i128).ok()
+                }
+            }
+        )*
+    };
+}
+
+coordinate!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+#[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Point2<T> {
+    pub x: T,
+    pub y: T,
+}
+
+#[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Point3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T: Coordinate> Point2<T> {
+    pub fn new(x: T, y: T) -> Self {
+        Point2 { x, y }
+    }
+
+    pub fn manhattan_distance(&self, other: &Self) -> usize {
+        self.x.distance(other.x) + self.y.distance(other.y)
+    }
+
+    pub fn chebyshev_distance(&self, other: &Self) -> usize {
+        self.x.distance(other.x).max(self.y.distance(other.y))
+    }
+
+    /// Moves at most one unit along each axis toward `other`, the way a rope knot follows the
+    /// knot ahead of it.
+    pub fn step_toward(&self, other: &Self) -> Self {
+        Point2::new(self.x.step_toward(other.x), self.y.step_toward(other.y))
+    }
+
+    pub fn offset(&self, delta: Point2<isize>) -> Option<Self> {
+        Some(Point2::new(self.x.offset(delta.x)?, self.y.offset(delta.y)?))
+    }
+
+    /// Converts between coordinate types (e.g. `isize` to `usize`), returning `None` if either
+    /// axis doesn't fit.
+    pub fn try_cast<U: Coordinate + TryFrom<T>>(&self) -> Option<Point2<U>> {
+        Some(Point2::new(
+            U::try_from(self.x).ok()?,
+            U::try_from(self.y).ok()?,
+        ))
+    }
+}
+
+impl<T: Coordinate> Point3<T> {
+    pub fn new(x: T, y: T, z: T) -> Self {
+        Point3 { x, y, z }
+    }
+
+    pub fn manhattan_distance(&self, other: &Self) -> usize {
+        self.x.distance(other.x) + self.y.distance(other.y) + self.z.distance(other.z)
+    }
+
+    pub fn chebyshev_distance(&self, other: &Self) -> usize {
+        self.x
+            .distance(other.x)
+            .max(self.y.distance(other.y))
+            .max(self.z.distance(other.z))
+    }
+
+    pub fn step_toward(&self, other: &Self) -> Self {
+        Point3::new(
+            self.x.step_toward(other.x),
+            self.y.step_toward(other.y),
+            self.z.step_toward(other.z),
+        )
+    }
+
+    pub fn try_cast<U: Coordinate + TryFrom<T>>(&self) -> Option<Point3<U>> {
+        Some(Point3::new(
+            U::try_from(self.x).ok()?,
+            U::try_from(self.y).ok()?,
+            U::try_from(self.z).ok()?,
+        ))
+    }
+}
+
+impl<T: Coordinate> From<(T, T)> for Point2<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Point2::new(x, y)
+    }
+}
+
+impl<T: Coordinate> From<(T, T, T)> for Point3<T> {
+    fn from((x, y, z): (T, T, T)) -> Self {
+        Point3::new(x, y, z)
+    }
+}
+
+impl<T: Coordinate> Add for Point2<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Point2::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl<T: Coordinate> Sub for Point2<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Point2::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl<T: Coordinate> Mul<T> for Point2<T> {
+    type Output = Self;
+
+    fn mul(self, scalar: T) -> Self {
+        Point2::new(self.x * scalar, self.y * scalar)
+    }
+}
+
+impl<T: Coordinate> AddAssign for Point2<T> {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl<T: Coordinate> SubAssign for Point2<T> {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl<T: Coordinate> Add for Point3<T> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Point3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl<T: Coordinate> Sub for Point3<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Point3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl<T: Coordinate> Mul<T> for Point3<T> {
+    type Output = Self;
+
+    fn mul(self, scalar: T) -> Self {
+        Point3::new(self.x * scalar, self.y * scalar, self.z * scalar)
+    }
+}
+
+impl<T: Coordinate> AddAssign for Point3<T> {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl<T: Coordinate> SubAssign for Point3<T> {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic() {
+        let a = Point2::new(1isize, -2);
+        let b = Point2::new(3, 4);
+
+        assert_eq!(a + b, Point2::new(4, 2));
+        assert_eq!(a - b, Point2::new(-2, -6));
+        assert_eq!(b * 3, Point2::new(9, 12));
+        assert_eq!(
+            Point3::new(1u8, 2, 3) + Point3::new(1, 1, 1),
+            Point3::new(2, 3, 4)
+        );
+    }
+
+    #[test]
+    fn distances() {
+        let a = Point2::new(-1isize, 5);
+        let b = Point2::new(2, 1);
+
+        assert_eq!(a.manhattan_distance(&b), 7);
+        assert_eq!(a.chebyshev_distance(&b), 4);
+        assert_eq!(
+            Point3::new(0usize, 0, 0).manhattan_distance(&Point3::new(1, 2, 3)),
+            6
+        );
+    }
+
+    #[test]
+    fn stepping_toward_another_point() {
+        let tail = Point2::new(0isize, 0);
+
+        assert_eq!(tail.step_toward(&Point2::new(-3, 0)), Point2::new(-1, 0));
+        assert_eq!(tail.step_toward(&Point2::new(0, 2)), Point2::new(0, 1));
+        assert_eq!(tail.step_toward(&Point2::new(-3, 2)), Point2::new(-1, 1));
+        assert_eq!(tail.step_toward(&tail), tail);
+        assert_eq!(
+            Point2::new(5usize, 0).step_toward(&Point2::new(0, 0)),
+            Point2::new(4, 0)
+        );
+    }
+
+    #[test]
+    fn offsets() {
+        assert_eq!(
+            Point2::new(0usize, 3).offset(Point2::new(1, -1)),
+            Some(Point2::new(1, 2))
+        );
+        assert_eq!(Point2::new(0usize, 3).offset(Point2::new(-1, 0)), None);
+    }
+
+    #[test]
+    fn checked_casts() {
+        assert_eq!(
+            Point2::new(3isize, 4).try_cast::<usize>(),
+            Some(Point2::new(3usize, 4))
+        );
+        assert_eq!(Point2::new(-1isize, 4).try_cast::<usize>(), None);
+        assert_eq!(Point2::new(usize::MAX, 0).try_cast::<isize>(), None);
+    }
+}
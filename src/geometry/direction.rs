@@ -0,0 +1,223 @@
+use super::{Coordinate, Point2};
+
+/// Compass directions in screen coordinates: `North` is up, so its unit vector has a negative y.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Direction4 {
+    North,
+    East,
+    South,
+    West,
+}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Direction8 {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction4 {
+    /// Clockwise, starting from `North`.
+    pub const ALL: [Direction4; 4] = [
+        Direction4::North,
+        Direction4::East,
+        Direction4::South,
+        Direction4::West,
+    ];
+
+    /// Accepts `U/D/L/R`, `^v<>` and `N/E/S/W`.
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            'U' | '^' | 'N' => Some(Direction4::North),
+            'R' | '>' | 'E' => Some(Direction4::East),
+            'D' | 'v' | 'S' => Some(Direction4::South),
+            'L' | '<' | 'W' => Some(Direction4::West),
+            _ => None,
+        }
+    }
+
+    pub fn all() -> impl Iterator<Item = Direction4> {
+        Self::ALL.into_iter()
+    }
+
+    fn rotate(self, quarter_turns: usize) -> Self {
+        Self::ALL[(self as usize + quarter_turns) % 4]
+    }
+
+    pub fn turn_right(self) -> Self {
+        self.rotate(1)
+    }
+
+    pub fn opposite(self) -> Self {
+        self.rotate(2)
+    }
+
+    pub fn turn_left(self) -> Self {
+        self.rotate(3)
+    }
+
+    pub fn unit(self) -> Point2<isize> {
+        match self {
+            Direction4::North => Point2::new(0, -1),
+            Direction4::East => Point2::new(1, 0),
+            Direction4::South => Point2::new(0, 1),
+            Direction4::West => Point2::new(-1, 0),
+        }
+    }
+
+    /// The neighboring point in this direction, or `None` if it falls outside the coordinate
+    /// type (e.g. below zero on a `usize` grid).
+    pub fn step<T: Coordinate>(self, point: Point2<T>) -> Option<Point2<T>> {
+        point.offset(self.unit())
+    }
+}
+
+impl Direction8 {
+    /// Clockwise, starting from `North`.
+    pub const ALL: [Direction8; 8] = [
+        Direction8::North,
+        Direction8::NorthEast,
+        Direction8::East,
+        Direction8::SouthEast,
+        Direction8::South,
+        Direction8::SouthWest,
+        Direction8::West,
+        Direction8::NorthWest,
+    ];
+
+    pub fn from_char(c: char) -> Option<Self> {
+        Direction4::from_char(c).map(Direction8::from)
+    }
+
+    pub fn all() -> impl Iterator<Item = Direction8> {
+        Self::ALL.into_iter()
+    }
+
+    fn rotate(self, eighth_turns: usize) -> Self {
+        Self::ALL[(self as usize + eighth_turns) % 8]
+    }
+
+    pub fn turn_right(self) -> Self {
+        self.rotate(2)
+    }
+
+    pub fn opposite(self) -> Self {
+        self.rotate(4)
+    }
+
+    pub fn turn_left(self) -> Self {
+        self.rotate(6)
+    }
+
+    pub fn unit(self) -> Point2<isize> {
+        match self {
+            Direction8::North => Point2::new(0, -1),
+            Direction8::NorthEast => Point2::new(1, -1),
+            Direction8::East => Point2::new(1, 0),
+            Direction8::SouthEast => Point2::new(1, 1),
+            Direction8::South => Point2::new(0, 1),
+            Direction8::SouthWest => Point2::new(-1, 1),
+            Direction8::West => Point2::new(-1, 0),
+            Direction8::NorthWest => Point2::new(-1, -1),
+        }
+    }
+
+    pub fn step<T: Coordinate>(self, point: Point2<T>) -> Option<Point2<T>> {
+        point.offset(self.unit())
+    }
+
+    /// The direction to walk from `from` to reach `to` in a straight line, if they share a row,
+    /// a column or a 45° diagonal.
+    pub fn between<T: Coordinate>(from: Point2<T>, to: Point2<T>) -> Option<Self> {
+        let (dx, dy) = (from.x.distance(to.x), from.y.distance(to.y));
+
+        if from == to || (dx != 0 && dy != 0 && dx != dy) {
+            return None;
+        }
+
+        let unit = Point2::new(
+            (to.x > from.x) as isize - (to.x < from.x) as isize,
+            (to.y > from.y) as isize - (to.y < from.y) as isize,
+        );
+
+        Self::all().find(|direction| direction.unit() == unit)
+    }
+}
+
+impl From<Direction4> for Direction8 {
+    fn from(direction: Direction4) -> Self {
+        match direction {
+            Direction4::North => Direction8::North,
+            Direction4::East => Direction8::East,
+            Direction4::South => Direction8::South,
+            Direction4::West => Direction8::West,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turning() {
+        assert_eq!(Direction4::North.turn_left(), Direction4::West);
+        assert_eq!(Direction4::West.turn_right(), Direction4::North);
+        assert_eq!(Direction4::East.opposite(), Direction4::West);
+        assert_eq!(Direction8::NorthEast.turn_right(), Direction8::SouthEast);
+        assert_eq!(Direction8::North.turn_left(), Direction8::West);
+        assert_eq!(Direction8::SouthWest.opposite(), Direction8::NorthEast);
+    }
+
+    #[test]
+    fn parsing_characters() {
+        assert_eq!(
+            "U^Nv>L".chars().map(Direction4::from_char).collect::<Vec<_>>(),
+            vec![
+                Some(Direction4::North),
+                Some(Direction4::North),
+                Some(Direction4::North),
+                Some(Direction4::South),
+                Some(Direction4::East),
+                Some(Direction4::West),
+            ]
+        );
+        assert_eq!(Direction4::from_char('x'), None);
+    }
+
+    #[test]
+    fn units_cancel_with_their_opposites() {
+        for direction in Direction8::all() {
+            assert_eq!(
+                direction.unit() + direction.opposite().unit(),
+                Point2::new(0, 0)
+            );
+        }
+    }
+
+    #[test]
+    fn stepping_and_direction_between() {
+        assert_eq!(Direction4::West.step(Point2::new(0usize, 0)), None);
+        assert_eq!(
+            Direction4::South.step(Point2::new(0usize, 0)),
+            Some(Point2::new(0, 1))
+        );
+        assert_eq!(
+            Direction8::between(Point2::new(1usize, 1), Point2::new(3, 3)),
+            Some(Direction8::SouthEast)
+        );
+        assert_eq!(
+            Direction8::between(Point2::new(3usize, 1), Point2::new(3, 0)),
+            Some(Direction8::North)
+        );
+        assert_eq!(
+            Direction8::between(Point2::new(0usize, 0), Point2::new(1, 2)),
+            None
+        );
+    }
+}
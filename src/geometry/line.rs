@@ -0,0 +1,260 @@
+use super::{Coordinate, Point2};
+
+/// A straight line between two points, inclusive of both ends.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct Segment<T> {
+    pub start: Point2<T>,
+    pub end: Point2<T>,
+}
+
+impl<T> Segment<T>
+where
+    T: Coordinate + TryFrom<isize>,
+    isize: TryFrom<T>,
+{
+    pub fn new(start: Point2<T>, end: Point2<T>) -> Self {
+        Segment { start, end }
+    }
+
+    pub fn is_axis_aligned(&self) -> bool {
+        self.start.x == self.end.x || self.start.y == self.end.y
+    }
+
+    pub fn is_diagonal(&self) -> bool {
+        self.start.x.distance(self.end.x) == self.start.y.distance(self.end.y)
+    }
+
+    /// Every integer point on the segment, from `start` to `end`. Axis-aligned and 45° segments
+    /// come out exact; anything else follows Bresenham's line.
+    pub fn points(&self) -> impl Iterator<Item = Point2<T>> {
+        Bresenham::new(self.start.try_cast(), self.end.try_cast())
+            .filter_map(|point| point.try_cast())
+    }
+
+    /// Whether the segments share at least one point, including touching at an end or
+    /// overlapping while collinear.
+    pub fn intersects(&self, other: &Self) -> bool {
+        let (Some(a), Some(b), Some(c), Some(d)) = (
+            wide(self.start),
+            wide(self.end),
+            wide(other.start),
+            wide(other.end),
+        ) else {
+            return false;
+        };
+
+        let (o1, o2, o3, o4) = (
+            orientation(a, b, c),
+            orientation(a, b, d),
+            orientation(c, d, a),
+            orientation(c, d, b),
+        );
+
+        (o1 != o2 && o3 != o4)
+            || (o1 == 0 && within(a, b, c))
+            || (o2 == 0 && within(a, b, d))
+            || (o3 == 0 && within(c, d, a))
+            || (o4 == 0 && within(c, d, b))
+    }
+
+    /// The single integer point where two non-parallel segments cross. Parallel or collinear
+    /// segments, and crossings that fall between grid points, return `None`.
+    pub fn crossing(&self, other: &Self) -> Option<Point2<T>> {
+        if !self.intersects(other) {
+            return None;
+        }
+
+        let (a, b, c, d) = (
+            wide(self.start)?,
+            wide(self.end)?,
+            wide(other.start)?,
+            wide(other.end)?,
+        );
+        let r = (b.0 - a.0, b.1 - a.1);
+        let s = (d.0 - c.0, d.1 - c.1);
+        let denominator = r.0 * s.1 - r.1 * s.0;
+
+        if denominator == 0 {
+            return None;
+        }
+
+        let numerator = (c.0 - a.0) * s.1 - (c.1 - a.1) * s.0;
+        let (x, y) = (
+            a.0 * denominator + numerator * r.0,
+            a.1 * denominator + numerator * r.1,
+        );
+
+        if x % denominator != 0 || y % denominator != 0 {
+            return None;
+        }
+
+        // the crossing lies within both segments, so it fits wherever their ends did
+        Point2::new((x / denominator) as isize, (y / denominator) as isize).try_cast()
+    }
+}
+
+/// Expands `a -> b -> c` into every point along each leg, without repeating the joints.
+pub fn polyline<T>(points: &[Point2<T>]) -> Vec<Point2<T>>
+where
+    T: Coordinate + TryFrom<isize>,
+    isize: TryFrom<T>,
+{
+    let mut results = vec![];
+
+    for (idx, pair) in points.windows(2).enumerate() {
+        let skip = if idx == 0 { 0 } else { 1 };
+        results.extend(Segment::new(pair[0], pair[1]).points().skip(skip));
+    }
+
+    if let [single] = points {
+        results.push(*single);
+    }
+
+    results
+}
+
+struct Bresenham {
+    current: Option<Point2<isize>>,
+    end: Point2<isize>,
+    delta: Point2<isize>,
+    step: Point2<isize>,
+    error: isize,
+}
+
+impl Bresenham {
+    fn new(start: Option<Point2<isize>>, end: Option<Point2<isize>>) -> Self {
+        let (current, end) = match (start, end) {
+            (Some(start), Some(end)) => (Some(start), end),
+            _ => (None, Point2::default()),
+        };
+        let start = current.unwrap_or_default();
+        let delta = Point2::new((end.x - start.x).abs(), -(end.y - start.y).abs());
+
+        Bresenham {
+            current,
+            end,
+            delta,
+            step: Point2::new((end.x - start.x).signum(), (end.y - start.y).signum()),
+            error: delta.x + delta.y,
+        }
+    }
+}
+
+impl Iterator for Bresenham {
+    type Item = Point2<isize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let point = self.current?;
+
+        if point == self.end {
+            self.current = None;
+            return Some(point);
+        }
+
+        let mut next = point;
+        let doubled = 2 * self.error;
+
+        if doubled >= self.delta.y {
+            self.error += self.delta.y;
+            next.x += self.step.x;
+        }
+
+        if doubled <= self.delta.x {
+            self.error += self.delta.x;
+            next.y += self.step.y;
+        }
+
+        self.current = Some(next);
+        Some(point)
+    }
+}
+
+fn wide<T>(point: Point2<T>) -> Option<(i128, i128)>
+where
+    T: Coordinate,
+    isize: TryFrom<T>,
+{
+    let point = point.try_cast::<isize>()?;
+    Some((point.x as i128, point.y as i128))
+}
+
+fn orientation(a: (i128, i128), b: (i128, i128), c: (i128, i128)) -> i8 {
+    let cross = (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0);
+    cross.signum() as i8
+}
+
+fn within(a: (i128, i128), b: (i128, i128), c: (i128, i128)) -> bool {
+    (a.0.min(b.0)..=a.0.max(b.0)).contains(&c.0) && (a.1.min(b.1)..=a.1.max(b.1)).contains(&c.1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(coordinates: &[(isize, isize)]) -> Vec<Point2<isize>> {
+        coordinates.iter().map(|&p| p.into()).collect()
+    }
+
+    #[test]
+    fn axis_aligned_and_diagonal_segments() {
+        assert_eq!(
+            Segment::new(Point2::new(498, 4), Point2::new(498, 6))
+                .points()
+                .collect::<Vec<_>>(),
+            points(&[(498, 4), (498, 5), (498, 6)])
+        );
+        assert_eq!(
+            Segment::new(Point2::new(3, 3), Point2::new(1, 1))
+                .points()
+                .collect::<Vec<_>>(),
+            points(&[(3, 3), (2, 2), (1, 1)])
+        );
+    }
+
+    #[test]
+    fn general_lines() {
+        assert_eq!(
+            Segment::new(Point2::new(0, 0), Point2::new(6, 2))
+                .points()
+                .collect::<Vec<_>>(),
+            points(&[(0, 0), (1, 0), (2, 1), (3, 1), (4, 1), (5, 2), (6, 2)])
+        );
+    }
+
+    #[test]
+    fn polylines_share_their_joints() {
+        assert_eq!(
+            polyline(&[
+                Point2::new(498usize, 4),
+                Point2::new(498, 6),
+                Point2::new(496, 6)
+            ]),
+            vec![
+                Point2::new(498, 4),
+                Point2::new(498, 5),
+                Point2::new(498, 6),
+                Point2::new(497, 6),
+                Point2::new(496, 6),
+            ]
+        );
+    }
+
+    #[test]
+    fn intersections() {
+        let horizontal = Segment::new(Point2::new(0, 2), Point2::new(4, 2));
+        let vertical = Segment::new(Point2::new(2, 0), Point2::new(2, 4));
+        let parallel = Segment::new(Point2::new(0, 3), Point2::new(4, 3));
+        let overlapping = Segment::new(Point2::new(3, 2), Point2::new(8, 2));
+
+        assert!(horizontal.intersects(&vertical));
+        assert_eq!(horizontal.crossing(&vertical), Some(Point2::new(2, 2)));
+        assert!(!horizontal.intersects(&parallel));
+        assert!(horizontal.intersects(&overlapping));
+        assert_eq!(horizontal.crossing(&overlapping), None);
+
+        let off_grid = Segment::new(Point2::new(0, 0), Point2::new(1, 1));
+        let other = Segment::new(Point2::new(0, 1), Point2::new(1, 0));
+        assert!(off_grid.intersects(&other));
+        assert_eq!(off_grid.crossing(&other), None);
+    }
+}
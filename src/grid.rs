@@ -0,0 +1,216 @@
+use crate::geometry::{direction::Direction8, Point2};
+
+pub mod region;
+
+/// A dense, rectangular grid stored row by row, indexed with `Point2 { x: column, y: row }`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    rows: Vec<Vec<T>>,
+}
+
+/// What a ray saw before it stopped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sightline {
+    /// Cells walked over that didn't match, nearest first.
+    pub passed: Vec<Point2<usize>>,
+    /// The first cell that matched, or `None` if the ray left the grid.
+    pub stopped_at: Option<Point2<usize>>,
+}
+
+impl Sightline {
+    /// How many cells can be seen, counting the blocking cell itself.
+    pub fn distance(&self) -> usize {
+        self.passed.len() + usize::from(self.stopped_at.is_some())
+    }
+
+    pub fn reached_edge(&self) -> bool {
+        self.stopped_at.is_none()
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn new(rows: Vec<Vec<T>>) -> Self {
+        Grid { rows }
+    }
+
+    pub fn width(&self) -> usize {
+        self.rows.first().map_or(0, |row| row.len())
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn rows(&self) -> &[Vec<T>] {
+        &self.rows
+    }
+
+    pub fn rows_mut(&mut self) -> &mut [Vec<T>] {
+        &mut self.rows
+    }
+
+    pub fn into_rows(self) -> Vec<Vec<T>> {
+        self.rows
+    }
+
+    pub fn get(&self, point: Point2<usize>) -> Option<&T> {
+        self.rows.get(point.y).and_then(|row| row.get(point.x))
+    }
+
+    pub fn get_mut(&mut self, point: Point2<usize>) -> Option<&mut T> {
+        self.rows
+            .get_mut(point.y)
+            .and_then(|row| row.get_mut(point.x))
+    }
+
+    pub fn contains(&self, point: Point2<usize>) -> bool {
+        self.get(point).is_some()
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = Point2<usize>> + '_ {
+        self.rows
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| (0..row.len()).map(move |x| Point2::new(x, y)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point2<usize>, &T)> {
+        self.rows.iter().enumerate().flat_map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .map(move |(x, value)| (Point2::new(x, y), value))
+        })
+    }
+
+    /// The cells in a straight line from `from` (exclusive) to the edge of the grid.
+    pub fn ray(
+        &self,
+        from: Point2<usize>,
+        direction: impl Into<Direction8>,
+    ) -> impl Iterator<Item = (Point2<usize>, &T)> {
+        let direction = direction.into();
+
+        std::iter::successors(direction.step(from), move |&point| direction.step(point))
+            .map_while(|point| self.get(point).map(|value| (point, value)))
+    }
+
+    /// Walks from `from` until `stop` matches a cell or the ray leaves the grid.
+    pub fn cast(
+        &self,
+        from: Point2<usize>,
+        direction: impl Into<Direction8>,
+        mut stop: impl FnMut(&T) -> bool,
+    ) -> Sightline {
+        let mut passed = vec![];
+
+        for (point, value) in self.ray(from, direction) {
+            if stop(value) {
+                return Sightline {
+                    passed,
+                    stopped_at: Some(point),
+                };
+            }
+
+            passed.push(point);
+        }
+
+        Sightline {
+            passed,
+            stopped_at: None,
+        }
+    }
+
+    /// The first cell in each of the eight directions that `visible` matches, skipping over the
+    /// rest (e.g. the nearest seat past any floor).
+    pub fn first_visible(
+        &self,
+        from: Point2<usize>,
+        mut visible: impl FnMut(&T) -> bool,
+    ) -> Vec<&T> {
+        Direction8::all()
+            .filter_map(|direction| self.cast(from, direction, &mut visible).stopped_at)
+            .filter_map(|point| self.get(point))
+            .collect()
+    }
+
+    /// The (up to eight) cells touching `from`.
+    pub fn adjacent(&self, from: Point2<usize>) -> impl Iterator<Item = &T> {
+        Direction8::all()
+            .filter_map(move |direction| direction.step(from))
+            .filter_map(|point| self.get(point))
+    }
+}
+
+impl<T> From<Vec<Vec<T>>> for Grid<T> {
+    fn from(rows: Vec<Vec<T>>) -> Self {
+        Grid::new(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::direction::Direction4;
+
+    fn trees() -> Grid<u8> {
+        Grid::new(vec![
+            vec![3, 0, 3, 7, 3],
+            vec![2, 5, 5, 1, 2],
+            vec![6, 5, 3, 3, 2],
+            vec![3, 3, 5, 4, 9],
+            vec![3, 5, 3, 9, 0],
+        ])
+    }
+
+    #[test]
+    fn rays_stop_at_the_edge() {
+        let grid = trees();
+
+        assert_eq!(
+            grid.ray(Point2::new(2, 2), Direction4::North)
+                .map(|(_, &v)| v)
+                .collect::<Vec<_>>(),
+            vec![5, 3]
+        );
+        assert_eq!(
+            grid.ray(Point2::new(0, 0), Direction8::NorthWest).count(),
+            0
+        );
+        assert_eq!(
+            grid.ray(Point2::new(0, 0), Direction8::SouthEast)
+                .map(|(_, &v)| v)
+                .collect::<Vec<_>>(),
+            vec![5, 3, 4, 0]
+        );
+    }
+
+    #[test]
+    fn casting_until_blocked() {
+        let grid = trees();
+        let from = Point2::new(2, 3);
+
+        let up = grid.cast(from, Direction4::North, |&v| v >= 5);
+        assert_eq!(up.passed, vec![Point2::new(2, 2)]);
+        assert_eq!(up.stopped_at, Some(Point2::new(2, 1)));
+        assert_eq!(up.distance(), 2);
+
+        let left = grid.cast(from, Direction4::West, |&v| v >= 5);
+        assert!(left.reached_edge());
+        assert_eq!(left.distance(), 2);
+    }
+
+    #[test]
+    fn first_visible_skips_ignored_cells() {
+        let grid = Grid::new(vec![
+            vec!['#', '.', '.', 'L'],
+            vec!['.', 'L', '.', '.'],
+            vec!['.', '.', '.', '.'],
+        ]);
+
+        assert_eq!(
+            grid.first_visible(Point2::new(0, 0), |&c| c != '.'),
+            vec![&'L', &'L']
+        );
+        assert_eq!(grid.adjacent(Point2::new(0, 0)).count(), 3);
+    }
+}
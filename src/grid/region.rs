@@ -0,0 +1,269 @@
+use super::Grid;
+use crate::geometry::{
+    direction::{Direction4, Direction8},
+    Point2,
+};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    /// Up, down, left and right.
+    Four,
+    /// Diagonals too.
+    Eight,
+}
+
+impl Connectivity {
+    fn directions(&self) -> Vec<Direction8> {
+        match self {
+            Connectivity::Four => Direction4::all().map(Direction8::from).collect(),
+            Connectivity::Eight => Direction8::all().collect(),
+        }
+    }
+}
+
+/// A connected group of cells.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    /// Every cell in the region, in the order the fill reached them.
+    pub points: Vec<Point2<usize>>,
+    /// How many cell edges separate the region from the rest of the grid (or the outside).
+    pub perimeter: usize,
+    /// Whether any cell sits on the outer edge of the grid.
+    pub touches_border: bool,
+}
+
+impl Region {
+    pub fn area(&self) -> usize {
+        self.points.len()
+    }
+}
+
+/// Every region found by labelling a grid, along with which region each cell belongs to.
+#[derive(Debug, Clone)]
+pub struct Components {
+    labels: Grid<Option<usize>>,
+    regions: Vec<Region>,
+}
+
+impl Components {
+    /// The index into `regions` of the region containing `point`, if any.
+    pub fn label(&self, point: Point2<usize>) -> Option<usize> {
+        self.labels.get(point).copied().flatten()
+    }
+
+    pub fn regions(&self) -> &[Region] {
+        &self.regions
+    }
+
+    /// Regions with no path to the edge of the grid.
+    pub fn enclosed(&self) -> impl Iterator<Item = &Region> {
+        self.regions.iter().filter(|region| !region.touches_border)
+    }
+}
+
+impl<T> Grid<T> {
+    /// Every cell reachable from `seed` through cells `passable` accepts. Returns an empty region
+    /// if the seed itself isn't passable.
+    pub fn flood_fill(
+        &self,
+        seed: Point2<usize>,
+        connectivity: Connectivity,
+        passable: impl Fn(&T) -> bool,
+    ) -> Region {
+        let mut visited = self.map_cells(|_| false);
+
+        match self.get(seed) {
+            Some(value) if passable(value) => {
+                self.fill(seed, connectivity, &mut visited, |_, to| passable(to))
+            }
+            _ => Region {
+                points: vec![],
+                perimeter: 0,
+                touches_border: false,
+            },
+        }
+    }
+
+    /// Labels every group of connected cells that `passable` accepts.
+    pub fn components(
+        &self,
+        connectivity: Connectivity,
+        passable: impl Fn(&T) -> bool,
+    ) -> Components {
+        self.label(connectivity, |value| passable(value), |_, to| passable(to))
+    }
+
+    /// Labels every group of connected cells holding the same value, so every cell ends up in
+    /// exactly one region.
+    pub fn regions(&self, connectivity: Connectivity) -> Components
+    where
+        T: PartialEq,
+    {
+        self.label(connectivity, |_| true, |from, to| from == to)
+    }
+
+    fn label(
+        &self,
+        connectivity: Connectivity,
+        include: impl Fn(&T) -> bool,
+        joins: impl Fn(&T, &T) -> bool,
+    ) -> Components {
+        let mut visited = self.map_cells(|_| false);
+        let mut labels = self.map_cells(|_| None);
+        let mut regions = vec![];
+
+        for (point, value) in self.iter() {
+            if visited.get(point) == Some(&true) || !include(value) {
+                continue;
+            }
+
+            let region = self.fill(point, connectivity, &mut visited, &joins);
+
+            for &member in &region.points {
+                if let Some(label) = labels.get_mut(member) {
+                    *label = Some(regions.len());
+                }
+            }
+
+            regions.push(region);
+        }
+
+        Components { labels, regions }
+    }
+
+    fn fill(
+        &self,
+        seed: Point2<usize>,
+        connectivity: Connectivity,
+        visited: &mut Grid<bool>,
+        joins: impl Fn(&T, &T) -> bool,
+    ) -> Region {
+        let directions = connectivity.directions();
+        let mut points = vec![];
+        let mut stack = vec![seed];
+
+        if let Some(seen) = visited.get_mut(seed) {
+            *seen = true;
+        }
+
+        while let Some(point) = stack.pop() {
+            points.push(point);
+            let from = self.get(point).expect("inside the grid");
+
+            for direction in &directions {
+                let Some(next) = direction.step(point) else {
+                    continue;
+                };
+
+                match (self.get(next), visited.get_mut(next)) {
+                    (Some(to), Some(seen)) if !*seen && joins(from, to) => {
+                        *seen = true;
+                        stack.push(next);
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        let members = points.iter().copied().collect::<HashSet<_>>();
+        let perimeter = points
+            .iter()
+            .flat_map(|&point| Direction4::all().map(move |direction| direction.step(point)))
+            .filter(|next| !next.is_some_and(|next| members.contains(&next)))
+            .count();
+        let touches_border = points.iter().any(|point| {
+            point.x == 0
+                || point.y == 0
+                || point.x + 1 == self.rows[point.y].len()
+                || point.y + 1 == self.height()
+        });
+
+        Region {
+            points,
+            perimeter,
+            touches_border,
+        }
+    }
+
+    fn map_cells<U>(&self, f: impl Fn(&T) -> U) -> Grid<U> {
+        Grid::new(
+            self.rows
+                .iter()
+                .map(|row| row.iter().map(&f).collect())
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn garden() -> Grid<char> {
+        Grid::new(
+            ["AAAA", "BBCD", "BBCC", "EEEC"]
+                .iter()
+                .map(|row| row.chars().collect())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn regions_report_area_and_perimeter() {
+        let components = garden().regions(Connectivity::Four);
+        let summary = components
+            .regions()
+            .iter()
+            .map(|region| (region.area(), region.perimeter))
+            .collect::<Vec<_>>();
+
+        assert_eq!(summary, vec![(4, 10), (4, 8), (4, 10), (1, 4), (3, 8)]);
+        assert_eq!(components.label(Point2::new(3, 3)), Some(2));
+    }
+
+    #[test]
+    fn flood_fill_respects_connectivity() {
+        let grid = Grid::new(vec![
+            vec![true, false, false],
+            vec![false, true, false],
+            vec![false, false, true],
+        ]);
+
+        assert_eq!(
+            grid.flood_fill(Point2::new(0, 0), Connectivity::Four, |&v| v)
+                .area(),
+            1
+        );
+        assert_eq!(
+            grid.flood_fill(Point2::new(0, 0), Connectivity::Eight, |&v| v)
+                .area(),
+            3
+        );
+        assert_eq!(
+            grid.flood_fill(Point2::new(1, 0), Connectivity::Four, |&v| v)
+                .area(),
+            0
+        );
+    }
+
+    #[test]
+    fn enclosed_regions_do_not_touch_the_border() {
+        let grid = Grid::new(
+            ["#####", "#..##", "#####", "...##"]
+                .iter()
+                .map(|row| row.chars().collect::<Vec<_>>())
+                .collect(),
+        );
+        let components = grid.components(Connectivity::Four, |&c| c == '.');
+
+        assert_eq!(components.regions().len(), 2);
+        assert_eq!(
+            components
+                .enclosed()
+                .map(|region| region.area())
+                .collect::<Vec<_>>(),
+            vec![2]
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use crate::geometry::Coordinate;
+use std::ops::Range;
+
+pub mod cuboid;
+
+/// The integers two ranges have in common. Disjoint ranges give an empty range.
+pub fn overlap<T: Coordinate>(a: &Range<T>, b: &Range<T>) -> Range<T> {
+    a.start.max(b.start)..a.end.min(b.end)
+}
+
+/// A set of integers stored as sorted, disjoint, half-open ranges. Ranges that overlap or touch
+/// (`0..3` and `3..5`) are merged as they go in, so every set has exactly one representation.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct IntervalSet<T> {
+    intervals: Vec<Range<T>>,
+}
+
+impl<T: Coordinate> IntervalSet<T> {
+    pub fn new() -> Self {
+        IntervalSet { intervals: vec![] }
+    }
+
+    /// The merged ranges, in ascending order.
+    pub fn intervals(&self) -> &[Range<T>] {
+        &self.intervals
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Range<T>> {
+        self.intervals.iter()
+    }
+
+    pub fn insert(&mut self, range: Range<T>) {
+        if range.is_empty() {
+            return;
+        }
+
+        // everything from `first` up to `last` overlaps or touches the new range
+        let first = self
+            .intervals
+            .partition_point(|other| other.end < range.start);
+        let last = self
+            .intervals
+            .partition_point(|other| other.start <= range.end);
+
+        let merged = match &self.intervals[first..last] {
+            [] => range,
+            [head, ..] => head.start.min(range.start)..self.intervals[last - 1].end.max(range.end),
+        };
+
+        self.intervals.splice(first..last, [merged]);
+    }
+
+    pub fn remove(&mut self, range: Range<T>) {
+        if range.is_empty() {
+            return;
+        }
+
+        let mut kept = Vec::with_capacity(self.intervals.len() + 1);
+
+        for interval in self.intervals.drain(..) {
+            if interval.end <= range.start || range.end <= interval.start {
+                kept.push(interval);
+                continue;
+            }
+
+            if interval.start < range.start {
+                kept.push(interval.start..range.start);
+            }
+
+            if range.end < interval.end {
+                kept.push(range.end..interval.end);
+            }
+        }
+
+        self.intervals = kept;
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+
+        for range in other.iter() {
+            result.insert(range.clone());
+        }
+
+        result
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut intervals = vec![];
+        let (mut left, mut right) = (0, 0);
+
+        while let (Some(a), Some(b)) = (self.intervals.get(left), other.intervals.get(right)) {
+            let common = overlap(a, b);
+
+            if !common.is_empty() {
+                intervals.push(common);
+            }
+
+            if a.end < b.end {
+                left += 1;
+            } else {
+                right += 1;
+            }
+        }
+
+        IntervalSet { intervals }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+
+        for range in other.iter() {
+            result.remove(range.clone());
+        }
+
+        result
+    }
+
+    /// How many integers the set holds.
+    pub fn len(&self) -> usize {
+        self.intervals
+            .iter()
+            .map(|range| range.start.distance(range.end))
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        self.covering(value).is_some()
+    }
+
+    /// Whether every integer in `range` is in the set.
+    pub fn contains_range(&self, range: &Range<T>) -> bool {
+        range.is_empty()
+            || self
+                .covering(&range.start)
+                .is_some_and(|interval| range.end <= interval.end)
+    }
+
+    /// Whether any integer in `range` is in the set.
+    pub fn overlaps(&self, range: &Range<T>) -> bool {
+        let idx = self
+            .intervals
+            .partition_point(|other| other.end <= range.start);
+
+        self.intervals
+            .get(idx)
+            .is_some_and(|interval| interval.start < range.end && !range.is_empty())
+    }
+
+    /// The holes between consecutive ranges, in ascending order.
+    pub fn gaps(&self) -> impl Iterator<Item = Range<T>> + '_ {
+        self.intervals
+            .windows(2)
+            .map(|pair| pair[0].end..pair[1].start)
+    }
+
+    fn covering(&self, value: &T) -> Option<&Range<T>> {
+        let idx = self.intervals.partition_point(|other| other.end <= *value);
+
+        self.intervals
+            .get(idx)
+            .filter(|interval| interval.start <= *value)
+    }
+}
+
+impl<T: Coordinate> From<Range<T>> for IntervalSet<T> {
+    fn from(range: Range<T>) -> Self {
+        let mut set = IntervalSet::new();
+        set.insert(range);
+        set
+    }
+}
+
+impl<T: Coordinate> FromIterator<Range<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(ranges: I) -> Self {
+        let mut set = IntervalSet::new();
+
+        for range in ranges {
+            set.insert(range);
+        }
+
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inserting_merges_overlapping_and_adjacent_ranges() {
+        let set = [-5..5, -3..2, -10..10, 2..19, 25..30, 19..20]
+            .into_iter()
+            .collect::<IntervalSet<isize>>();
+
+        assert_eq!(set.intervals(), &[-10..20, 25..30]);
+        assert_eq!(set.len(), 35);
+        assert_eq!(set.gaps().collect::<Vec<_>>(), vec![20..25]);
+    }
+
+    #[test]
+    fn containment() {
+        let set = [0..3, 5..8].into_iter().collect::<IntervalSet<usize>>();
+
+        assert!(set.contains(&0));
+        assert!(!set.contains(&3));
+        assert!(set.contains_range(&(5..8)));
+        assert!(!set.contains_range(&(2..6)));
+        assert!(set.overlaps(&(2..6)));
+        assert!(!set.overlaps(&(3..5)));
+    }
+
+    #[test]
+    fn set_operations() {
+        let a = [0..10, 20..30].into_iter().collect::<IntervalSet<isize>>();
+        let b = IntervalSet::from(5..25);
+
+        assert_eq!(a.union(&b), IntervalSet::from(0..30));
+        assert_eq!(a.intersection(&b).intervals(), &[5..10, 20..25]);
+        assert_eq!(a.difference(&b).intervals(), &[0..5, 25..30]);
+        assert!(a.difference(&a).is_empty());
+    }
+}
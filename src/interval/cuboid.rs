@@ -0,0 +1,204 @@
+use super::overlap;
+use std::ops::{Range, RangeInclusive};
+
+/// An axis-aligned box in `N` dimensions, one half-open range per axis. A rectangle is a
+/// `Cuboid<2>`; anything with an empty axis holds nothing.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Cuboid<const N: usize> {
+    pub axes: [Range<isize>; N],
+}
+
+impl<const N: usize> Cuboid<N> {
+    pub fn new(axes: [Range<isize>; N]) -> Self {
+        Cuboid { axes }
+    }
+
+    /// From inclusive bounds, the way puzzles tend to write them (`x=10..12`).
+    pub fn from_inclusive(axes: [RangeInclusive<isize>; N]) -> Self {
+        Cuboid {
+            axes: axes.map(|axis| *axis.start()..*axis.end() + 1),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.axes.iter().any(|axis| axis.is_empty())
+    }
+
+    /// How many integer points the box holds.
+    pub fn volume(&self) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+
+        self.axes
+            .iter()
+            .map(|axis| axis.start.abs_diff(axis.end))
+            .product()
+    }
+
+    pub fn contains(&self, point: [isize; N]) -> bool {
+        self.axes
+            .iter()
+            .zip(point)
+            .all(|(axis, value)| axis.contains(&value))
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let mut axes = self.axes.clone();
+
+        for (axis, other) in axes.iter_mut().zip(&other.axes) {
+            *axis = overlap(axis, other);
+        }
+
+        let common = Cuboid { axes };
+        (!common.is_empty()).then_some(common)
+    }
+
+    /// What's left of this box once `other` is cut out, as at most `2 * N` disjoint boxes.
+    ///
+    /// Works one axis at a time: the slabs on either side of `other` along that axis are split
+    /// off whole, and the rest is narrowed to `other`'s range before moving to the next axis.
+    pub fn subtract(&self, other: &Self) -> Vec<Self> {
+        if self.intersection(other).is_none() {
+            return if self.is_empty() {
+                vec![]
+            } else {
+                vec![self.clone()]
+            };
+        }
+
+        let mut pieces = vec![];
+        let mut rest = self.clone();
+
+        for idx in 0..N {
+            let (axis, cut) = (rest.axes[idx].clone(), &other.axes[idx]);
+
+            for side in [axis.start..cut.start, cut.end..axis.end] {
+                let mut piece = rest.clone();
+                piece.axes[idx] = overlap(&axis, &side);
+
+                if !piece.is_empty() {
+                    pieces.push(piece);
+                }
+            }
+
+            rest.axes[idx] = overlap(&axis, cut);
+        }
+
+        pieces
+    }
+}
+
+/// Tracks the volume covered by a series of boxes switched on and off, without ever splitting
+/// them up: every overlap is added back in with the opposite sign (inclusion–exclusion).
+#[derive(Debug, Clone, Default)]
+pub struct SignedVolumes<const N: usize> {
+    entries: Vec<(Cuboid<N>, isize)>,
+}
+
+impl<const N: usize> SignedVolumes<N> {
+    pub fn new() -> Self {
+        SignedVolumes { entries: vec![] }
+    }
+
+    /// Turns every point in `cuboid` on.
+    pub fn add(&mut self, cuboid: Cuboid<N>) {
+        self.cancel_overlaps(&cuboid);
+        self.entries.push((cuboid, 1));
+    }
+
+    /// Turns every point in `cuboid` off.
+    pub fn remove(&mut self, cuboid: Cuboid<N>) {
+        self.cancel_overlaps(&cuboid);
+    }
+
+    fn cancel_overlaps(&mut self, cuboid: &Cuboid<N>) {
+        let overlaps = self
+            .entries
+            .iter()
+            .filter_map(|(other, sign)| other.intersection(cuboid).map(|common| (common, -sign)))
+            .collect::<Vec<_>>();
+
+        self.entries.extend(overlaps);
+    }
+
+    /// How many points are currently on.
+    pub fn volume(&self) -> usize {
+        let total = self
+            .entries
+            .iter()
+            .map(|(cuboid, sign)| sign * cuboid.volume() as isize)
+            .sum::<isize>();
+
+        total as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn steps() -> Vec<(bool, Cuboid<3>)> {
+        vec![
+            (true, Cuboid::from_inclusive([10..=12, 10..=12, 10..=12])),
+            (true, Cuboid::from_inclusive([11..=13, 11..=13, 11..=13])),
+            (false, Cuboid::from_inclusive([9..=11, 9..=11, 9..=11])),
+            (true, Cuboid::from_inclusive([10..=10, 10..=10, 10..=10])),
+        ]
+    }
+
+    #[test]
+    fn signed_volumes_follow_on_and_off() {
+        let mut reactor = SignedVolumes::new();
+
+        for (on, cuboid) in steps() {
+            if on {
+                reactor.add(cuboid);
+            } else {
+                reactor.remove(cuboid);
+            }
+        }
+
+        assert_eq!(reactor.volume(), 39);
+    }
+
+    #[test]
+    fn subtracting_leaves_disjoint_pieces() {
+        let mut lit: Vec<Cuboid<3>> = vec![];
+
+        for (on, cuboid) in steps() {
+            lit = lit
+                .iter()
+                .flat_map(|piece| piece.subtract(&cuboid))
+                .collect();
+
+            if on {
+                lit.push(cuboid);
+            }
+        }
+
+        assert_eq!(lit.iter().map(Cuboid::volume).sum::<usize>(), 39);
+        assert!(lit
+            .iter()
+            .enumerate()
+            .all(|(idx, a)| lit[idx + 1..].iter().all(|b| a.intersection(b).is_none())));
+    }
+
+    #[test]
+    fn overlapping_rectangles() {
+        let claims = [
+            Cuboid::new([1..5, 3..7]),
+            Cuboid::new([3..7, 1..5]),
+            Cuboid::new([5..7, 5..7]),
+        ];
+
+        assert_eq!(
+            claims[0].intersection(&claims[1]),
+            Some(Cuboid::new([3..5, 3..5]))
+        );
+        assert_eq!(claims[0].intersection(&claims[2]), None);
+        assert_eq!(claims[0].subtract(&claims[1]).len(), 2);
+        assert!(claims[2].contains([6, 6]));
+        assert!(!claims[2].contains([7, 6]));
+    }
+}
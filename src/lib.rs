@@ -1 +1,14 @@
+pub mod automaton;
+pub mod cycle;
+pub mod dag;
+pub mod dot;
+pub mod filesystem;
+pub mod geometry;
+pub mod grid;
+pub mod interval;
+pub mod math;
+pub mod ocr;
 pub mod parser;
+pub mod search;
+pub mod visualizer;
+pub mod vm;
@@ -0,0 +1,150 @@
+pub fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Zero if either side is zero.
+pub fn lcm(a: usize, b: usize) -> usize {
+    if a == 0 || b == 0 {
+        0
+    } else {
+        a / gcd(a, b) * b
+    }
+}
+
+/// The gcd of everything, or zero for nothing at all.
+pub fn gcd_all(values: impl IntoIterator<Item = usize>) -> usize {
+    values.into_iter().fold(0, gcd)
+}
+
+/// The lcm of everything, or one for nothing at all: the smallest number every value divides.
+pub fn lcm_all(values: impl IntoIterator<Item = usize>) -> usize {
+    values.into_iter().fold(1, lcm)
+}
+
+/// Finds `(g, x, y)` with `a * x + b * y = g`, where `g` is the (non-negative) gcd of `a` and `b`.
+pub fn extended_gcd(a: isize, b: isize) -> (isize, isize, isize) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_x, x) = (x, old_x - quotient * x);
+        (old_y, y) = (y, old_y - quotient * y);
+    }
+
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// The `x` in `0..modulus` with `a * x ≡ 1`, if `a` and `modulus` are coprime.
+pub fn mod_inverse(a: isize, modulus: isize) -> Option<isize> {
+    if modulus <= 0 {
+        return None;
+    }
+
+    let (g, x, _) = extended_gcd(a.rem_euclid(modulus), modulus);
+    (g == 1).then(|| x.rem_euclid(modulus))
+}
+
+/// `base` to the power `exponent`, modulo `modulus`, by repeated squaring. Products are taken in
+/// 128 bits so nothing overflows along the way; only a zero modulus gives `None`.
+pub fn checked_modpow(base: usize, exponent: usize, modulus: usize) -> Option<usize> {
+    if modulus == 0 {
+        return None;
+    }
+
+    let modulus = modulus as u128;
+    let (mut result, mut base, mut exponent) = (1 % modulus, base as u128 % modulus, exponent);
+
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result * base % modulus;
+        }
+
+        base = base * base % modulus;
+        exponent >>= 1;
+    }
+
+    Some(result as usize)
+}
+
+/// Solves a system of congruences `x ≡ residue (mod modulus)`, returning the smallest
+/// non-negative `x` and the modulus it repeats with (the lcm of them all).
+///
+/// The moduli don't have to be coprime. Gives `None` if the congruences contradict each other, a
+/// modulus isn't positive, or the combined modulus doesn't fit in an `isize`.
+pub fn crt(congruences: impl IntoIterator<Item = (isize, isize)>) -> Option<(isize, isize)> {
+    congruences
+        .into_iter()
+        .try_fold((0, 1), |(x, modulus), (residue, other)| {
+            if other <= 0 {
+                return None;
+            }
+
+            let (g, p, _) = extended_gcd(modulus, other);
+            let difference = residue - x;
+
+            if difference % g != 0 {
+                return None;
+            }
+
+            // x + modulus * k satisfies both once k ≡ (difference / g) * p (mod other / g)
+            let step = (other / g) as i128;
+            let k = ((difference / g) as i128 * p as i128).rem_euclid(step);
+            let combined = modulus as i128 * step;
+            let x = (x as i128 + modulus as i128 * k).rem_euclid(combined);
+
+            Some((isize::try_from(x).ok()?, isize::try_from(combined).ok()?))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gcd_and_lcm() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(lcm(4, 6), 12);
+        assert_eq!(gcd_all([12, 18, 27]), 3);
+        assert_eq!(lcm_all([23, 19, 13, 17]), 96577);
+        assert_eq!(lcm_all([4, 6, 10]), 60);
+        assert_eq!((gcd_all([]), lcm_all([])), (0, 1));
+    }
+
+    #[test]
+    fn inverses_and_powers() {
+        assert_eq!(extended_gcd(240, 46), (2, -9, 47));
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(-3, 11), Some(7));
+        assert_eq!(mod_inverse(6, 9), None);
+        assert_eq!(checked_modpow(4, 13, 497), Some(445));
+        assert_eq!(checked_modpow(7, 20201227, 20201227), Some(7));
+        assert_eq!(
+            checked_modpow(usize::MAX, usize::MAX, usize::MAX - 1),
+            Some(1)
+        );
+        assert_eq!(checked_modpow(2, 10, 0), None);
+    }
+
+    #[test]
+    fn chinese_remainders() {
+        // the bus schedule 7,13,x,x,59,x,31,19: bus `id` at offset `i` wants `t + i ≡ 0 (mod id)`
+        let buses = [(0, 7), (1, 13), (4, 59), (6, 31), (7, 19)];
+        let congruences = buses.map(|(offset, id)| (-offset, id));
+
+        assert_eq!(crt(congruences).map(|(t, _)| t), Some(1068781));
+        assert_eq!(crt([(2, 4), (4, 6)]), Some((10, 12)));
+        assert_eq!(crt([(1, 4), (2, 6)]), None);
+        assert_eq!(crt([]), Some((0, 1)));
+    }
+}
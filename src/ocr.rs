@@ -0,0 +1,258 @@
+use crate::geometry::Point2;
+use crate::grid::Grid;
+use std::fmt;
+
+/// One of the block-letter alphabets puzzles draw their answers in.
+struct Font {
+    /// How wide most glyphs are. Some, like the small `Y`, run on into the gap after them.
+    width: usize,
+    height: usize,
+    /// From the left edge of one letter to the left edge of the next.
+    stride: usize,
+    /// Each glyph's rows of `#` (lit) and `.` (dark), joined with newlines.
+    glyphs: &'static [(char, &'static str)],
+}
+
+/// The 4×6 letters most screen puzzles use, one blank column apart.
+const SMALL: Font = Font {
+    width: 4,
+    height: 6,
+    stride: 5,
+    glyphs: &[
+        ('A', ".##.\n#..#\n#..#\n####\n#..#\n#..#"),
+        ('B', "###.\n#..#\n###.\n#..#\n#..#\n###."),
+        ('C', ".##.\n#..#\n#...\n#...\n#..#\n.##."),
+        ('E', "####\n#...\n###.\n#...\n#...\n####"),
+        ('F', "####\n#...\n###.\n#...\n#...\n#..."),
+        ('G', ".##.\n#..#\n#...\n#.##\n#..#\n.###"),
+        ('H', "#..#\n#..#\n####\n#..#\n#..#\n#..#"),
+        ('I', ".###\n..#.\n..#.\n..#.\n..#.\n.###"),
+        ('J', "..##\n...#\n...#\n...#\n#..#\n.##."),
+        ('K', "#..#\n#.#.\n##..\n#.#.\n#.#.\n#..#"),
+        ('L', "#...\n#...\n#...\n#...\n#...\n####"),
+        ('O', ".##.\n#..#\n#..#\n#..#\n#..#\n.##."),
+        ('P', "###.\n#..#\n#..#\n###.\n#...\n#..."),
+        ('R', "###.\n#..#\n#..#\n###.\n#.#.\n#..#"),
+        ('S', ".###\n#...\n#...\n.##.\n...#\n###."),
+        ('U', "#..#\n#..#\n#..#\n#..#\n#..#\n.##."),
+        ('Y', "#...#\n#...#\n.#.#.\n..#..\n..#..\n..#.."),
+        ('Z', "####\n...#\n..#.\n.#..\n#...\n####"),
+    ],
+};
+
+/// The 6×10 letters from the star-message puzzles, two blank columns apart.
+const LARGE: Font = Font {
+    width: 6,
+    height: 10,
+    stride: 8,
+    glyphs: &[
+        (
+            'A',
+            "..##..\n.#..#.\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#",
+        ),
+        (
+            'B',
+            "#####.\n#....#\n#....#\n#....#\n#####.\n#....#\n#....#\n#....#\n#....#\n#####.",
+        ),
+        (
+            'C',
+            ".####.\n#....#\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#....#\n.####.",
+        ),
+        (
+            'E',
+            "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n######",
+        ),
+        (
+            'F',
+            "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+        ),
+        (
+            'G',
+            ".####.\n#....#\n#.....\n#.....\n#.....\n#..###\n#....#\n#....#\n#...##\n.###.#",
+        ),
+        (
+            'H',
+            "#....#\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#\n#....#",
+        ),
+        (
+            'J',
+            "...###\n....#.\n....#.\n....#.\n....#.\n....#.\n....#.\n#...#.\n#...#.\n.###..",
+        ),
+        (
+            'K',
+            "#....#\n#...#.\n#..#..\n#.#...\n##....\n##....\n#.#...\n#..#..\n#...#.\n#....#",
+        ),
+        (
+            'L',
+            "#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n######",
+        ),
+        (
+            'N',
+            "#....#\n##...#\n##...#\n#.#..#\n#.#..#\n#..#.#\n#..#.#\n#...##\n#...##\n#....#",
+        ),
+        (
+            'P',
+            "#####.\n#....#\n#....#\n#....#\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+        ),
+        (
+            'R',
+            "#####.\n#....#\n#....#\n#....#\n#####.\n#..#..\n#...#.\n#...#.\n#....#\n#....#",
+        ),
+        (
+            'X',
+            "#....#\n#....#\n.#..#.\n.#..#.\n..##..\n..##..\n.#..#.\n.#..#.\n#....#\n#....#",
+        ),
+        (
+            'Z',
+            "######\n.....#\n.....#\n....#.\n...#..\n..#...\n.#....\n#.....\n#.....\n######",
+        ),
+    ],
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OcrError {
+    /// Neither alphabet is this many rows tall.
+    UnsupportedHeight(usize),
+    /// The letter at `position` (counting from 0) isn't in the alphabet. Holds the glyph as
+    /// `#`/`.` rows.
+    UnknownGlyph { position: usize, glyph: String },
+}
+
+impl fmt::Display for OcrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OcrError::UnsupportedHeight(height) => {
+                write!(f, "no block-letter alphabet is {} rows tall", height)
+            }
+            OcrError::UnknownGlyph { position, glyph } => {
+                write!(f, "unknown letter at position {}:\n{}", position, glyph)
+            }
+        }
+    }
+}
+
+impl std::error::Error for OcrError {}
+
+/// Reads the block letters off a screen of lit (`true`) and dark pixels. The alphabet is picked
+/// by the screen's height; rows shorter than the rest count as dark past their end.
+pub fn recognize(screen: &Grid<bool>) -> Result<String, OcrError> {
+    let font = match screen.height() {
+        6 => SMALL,
+        10 => LARGE,
+        height => return Err(OcrError::UnsupportedHeight(height)),
+    };
+    let width = screen.rows().iter().map(Vec::len).max().unwrap_or(0);
+    let letters = width.div_ceil(font.stride);
+
+    (0..letters)
+        .map(|position| {
+            let left = position * font.stride;
+            let lit = |x: usize, y: usize| screen.get(Point2::new(left + x, y)) == Some(&true);
+            // the usual width, or further if something's drawn in the gap
+            let width = (font.width..font.stride)
+                .rev()
+                .find(|&x| (0..font.height).any(|y| lit(x, y)))
+                .map_or(font.width, |x| x + 1);
+            let glyph = (0..font.height)
+                .map(|y| {
+                    (0..width)
+                        .map(|x| if lit(x, y) { '#' } else { '.' })
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            font.glyphs
+                .iter()
+                .find(|&&(_, known)| known == glyph)
+                .map(|&(letter, _)| letter)
+                .ok_or(OcrError::UnknownGlyph { position, glyph })
+        })
+        .collect()
+}
+
+/// Like [`recognize`], for a screen drawn as lines of `#` (lit) and `.` (dark).
+pub fn recognize_str(screen: &str) -> Result<String, OcrError> {
+    let rows = screen
+        .trim()
+        .lines()
+        .map(|line| line.trim().chars().map(|c| c == '#').collect())
+        .collect::<Vec<_>>();
+
+    recognize(&Grid::new(rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reading_small_letters() {
+        let screen = r#"
+###..####.#..#.#....
+#..#.#....#.#..#....
+#..#.###..##...#....
+###..#....#.#..#....
+#.#..#....#.#..#....
+#..#.####.#..#.####.
+"#;
+
+        assert_eq!(recognize_str(screen), Ok("REKL".to_string()));
+    }
+
+    #[test]
+    fn reading_a_letter_that_fills_the_gap() {
+        let screen = r#"
+#...##....#..#.
+#...##....#..#.
+.#.#.#....####.
+..#..#....#..#.
+..#..#....#..#.
+..#..####.#..#.
+"#;
+
+        assert_eq!(recognize_str(screen), Ok("YLH".to_string()));
+    }
+
+    #[test]
+    fn reading_large_letters() {
+        let screen = r#"
+#....#..######
+#....#.......#
+#....#.......#
+#....#......#.
+######.....#..
+#....#....#...
+#....#...#....
+#....#..#.....
+#....#..#.....
+#....#..######
+"#;
+
+        assert_eq!(recognize_str(screen), Ok("HZ".to_string()));
+    }
+
+    #[test]
+    fn unknown_glyphs_are_reported() {
+        let screen = r#"
+.##..####.
+#..#.#..#.
+#..#.####.
+#..#.#..#.
+#..#.#..#.
+.##..####.
+"#;
+
+        assert_eq!(
+            recognize_str(screen),
+            Err(OcrError::UnknownGlyph {
+                position: 1,
+                glyph: "####\n#..#\n####\n#..#\n#..#\n####".to_string()
+            })
+        );
+        assert_eq!(
+            recognize_str("#..#\n#..#"),
+            Err(OcrError::UnsupportedHeight(2))
+        );
+    }
+}
@@ -0,0 +1,266 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    hash::Hash,
+    ops::Add,
+};
+
+/// Everything a search settled, in enough detail to rebuild the paths it found.
+///
+/// Only settled states are reported: their costs are final, unlike states that were merely seen
+/// on the frontier when the search stopped.
+#[derive(Debug, Clone)]
+pub struct Explored<S, C> {
+    costs: HashMap<S, C>,
+    parents: HashMap<S, S>,
+    paths: HashMap<S, usize>,
+    order: Vec<S>,
+    goal: Option<S>,
+}
+
+impl<S, C> Explored<S, C>
+where
+    S: Clone + Eq + Hash,
+    C: Copy,
+{
+    /// The first state the goal predicate accepted, if the search got that far.
+    pub fn goal(&self) -> Option<&S> {
+        self.goal.as_ref()
+    }
+
+    pub fn goal_cost(&self) -> Option<C> {
+        self.goal.as_ref().and_then(|goal| self.cost(goal))
+    }
+
+    /// The cheapest cost from any start to `state`.
+    pub fn cost(&self, state: &S) -> Option<C> {
+        self.costs.get(state).copied()
+    }
+
+    /// One cheapest path to `state`, starting with the start it came from and ending with
+    /// `state` itself.
+    pub fn path_to(&self, state: &S) -> Option<Vec<S>> {
+        if !self.costs.contains_key(state) {
+            return None;
+        }
+
+        let mut path = vec![state.clone()];
+
+        while let Some(parent) = self.parents.get(path.last()?) {
+            path.push(parent.clone());
+        }
+
+        path.reverse();
+        Some(path)
+    }
+
+    pub fn path(&self) -> Option<Vec<S>> {
+        self.path_to(self.goal.as_ref()?)
+    }
+
+    /// How many distinct cheapest paths lead to `state`. Only exact when every step costs more
+    /// than nothing.
+    pub fn shortest_paths(&self, state: &S) -> usize {
+        self.paths.get(state).copied().unwrap_or(0)
+    }
+
+    /// Every settled state, in the order the search settled them.
+    pub fn visited(&self) -> &[S] {
+        &self.order
+    }
+}
+
+/// Breadth-first search where every step costs one.
+pub fn bfs<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut neighbors: impl FnMut(&S) -> I,
+    goal: impl FnMut(&S) -> bool,
+) -> Explored<S, usize>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    // ties in the queue break by insertion order, so unit costs come out first-in first-out
+    astar(
+        starts,
+        |state| neighbors(state).into_iter().map(|next| (next, 1)),
+        |_| 0,
+        goal,
+    )
+}
+
+/// Cheapest-first search from every start at once. Pass `|_| false` as the goal to settle
+/// everything reachable.
+pub fn dijkstra<S, C, I>(
+    starts: impl IntoIterator<Item = S>,
+    neighbors: impl FnMut(&S) -> I,
+    goal: impl FnMut(&S) -> bool,
+) -> Explored<S, C>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    astar(starts, neighbors, |_| C::default(), goal)
+}
+
+/// Dijkstra guided by `heuristic`, an estimate of the remaining cost to the goal. The estimate
+/// must never overshoot (and should never drop by more than a step costs) for the result to be
+/// the cheapest.
+pub fn astar<S, C, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut neighbors: impl FnMut(&S) -> I,
+    heuristic: impl Fn(&S) -> C,
+    mut goal: impl FnMut(&S) -> bool,
+) -> Explored<S, C>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut explored = Explored {
+        costs: HashMap::new(),
+        parents: HashMap::new(),
+        paths: HashMap::new(),
+        order: vec![],
+        goal: None,
+    };
+    // best known cost and number of paths at that cost, for states not yet settled
+    let mut frontier: HashMap<S, (C, usize)> = HashMap::new();
+    // the heap holds indices into `queued` so states don't need to be `Ord`
+    let mut queued = vec![];
+    let mut queue = BinaryHeap::new();
+
+    for start in starts {
+        if frontier.insert(start.clone(), (C::default(), 1)).is_none() {
+            queue.push(Reverse((heuristic(&start), C::default(), queued.len())));
+            queued.push(start);
+        }
+    }
+
+    while let Some(Reverse((_, cost, index))) = queue.pop() {
+        let state = queued[index].clone();
+
+        let Some(&(best, paths)) = frontier.get(&state) else {
+            continue;
+        };
+
+        if best < cost || explored.costs.contains_key(&state) {
+            continue;
+        }
+
+        frontier.remove(&state);
+        explored.costs.insert(state.clone(), cost);
+        explored.paths.insert(state.clone(), paths);
+        explored.order.push(state.clone());
+
+        if goal(&state) {
+            explored.goal = Some(state);
+            break;
+        }
+
+        for (next, step) in neighbors(&state) {
+            if explored.costs.contains_key(&next) {
+                continue;
+            }
+
+            let total = cost + step;
+
+            match frontier.get_mut(&next) {
+                Some((best, count)) if total == *best => *count += paths,
+                Some((best, _)) if total > *best => (),
+                _ => {
+                    frontier.insert(next.clone(), (total, paths));
+                    explored.parents.insert(next.clone(), state.clone());
+                    queue.push(Reverse((total + heuristic(&next), total, queued.len())));
+                    queued.push(next);
+                }
+            }
+        }
+    }
+
+    explored
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{direction::Direction4, Point2};
+
+    const MAZE: [&str; 5] = ["S..#....", ".#.#.##.", ".#...#..", ".####.#.", "......#E"];
+
+    fn open(point: Point2<usize>) -> bool {
+        MAZE.get(point.y)
+            .and_then(|row| row.as_bytes().get(point.x))
+            .is_some_and(|&cell| cell != b'#')
+    }
+
+    fn steps(point: &Point2<usize>) -> Vec<Point2<usize>> {
+        Direction4::all()
+            .filter_map(|direction| direction.step(*point))
+            .filter(|&next| open(next))
+            .collect()
+    }
+
+    #[test]
+    fn bfs_finds_the_shortest_path() {
+        let end = Point2::new(7, 4);
+        let explored = bfs([Point2::new(0, 0)], steps, |&point| point == end);
+        let path = explored.path().unwrap();
+
+        assert_eq!(explored.goal_cost(), Some(15));
+        assert_eq!(path.len(), 16);
+        assert_eq!(path.first(), Some(&Point2::new(0, 0)));
+        assert_eq!(path.last(), Some(&end));
+        assert!(path
+            .windows(2)
+            .all(|pair| pair[0].manhattan_distance(&pair[1]) == 1));
+    }
+
+    #[test]
+    fn dijkstra_counts_every_cheapest_path() {
+        let edges = HashMap::from([
+            ('a', vec![('b', 1), ('c', 2), ('d', 5)]),
+            ('b', vec![('d', 2)]),
+            ('c', vec![('d', 1)]),
+        ]);
+        let explored = dijkstra(
+            ['a'],
+            |node| edges.get(node).cloned().unwrap_or_default(),
+            |_| false,
+        );
+
+        assert_eq!(explored.cost(&'d'), Some(3));
+        assert_eq!(explored.shortest_paths(&'d'), 2);
+        assert_eq!(explored.path_to(&'b'), Some(vec!['a', 'b']));
+        assert_eq!(explored.visited(), &['a', 'b', 'c', 'd']);
+    }
+
+    #[test]
+    fn astar_settles_fewer_states() {
+        let end = Point2::new(7, 4);
+        let weighted = |point: &Point2<usize>| steps(point).into_iter().map(|next| (next, 1));
+
+        let blind = dijkstra([Point2::new(0, 0)], weighted, |&point| point == end);
+        let guided = astar(
+            [Point2::new(0, 0)],
+            weighted,
+            |point| point.manhattan_distance(&end),
+            |&point| point == end,
+        );
+
+        assert_eq!(guided.goal_cost(), blind.goal_cost());
+        assert!(guided.visited().len() < blind.visited().len());
+    }
+
+    #[test]
+    fn unreachable_goals_have_no_path() {
+        let explored = bfs([Point2::new(0, 0)], steps, |&point| {
+            point == Point2::new(3, 0)
+        });
+
+        assert_eq!(explored.goal(), None);
+        assert_eq!(explored.path(), None);
+        assert_eq!(explored.path_to(&Point2::new(9, 9)), None);
+    }
+}
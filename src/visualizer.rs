@@ -0,0 +1,411 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Stdout, Write},
+    path::PathBuf,
+    thread,
+    time::Duration,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub glyph: char,
+    pub color: Rgb,
+}
+
+impl Cell {
+    pub fn new(glyph: char, color: Rgb) -> Self {
+        Cell { glyph, color }
+    }
+
+    pub fn blank() -> Self {
+        Cell::new(' ', Rgb::BLACK)
+    }
+}
+
+/// A single snapshot of a simulation, rendered both as text (for the terminal) and as pixels
+/// (for image export).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+}
+
+impl Frame {
+    pub fn new(width: usize, height: usize, fill: Cell) -> Self {
+        Frame {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    pub fn from_rows<T>(rows: &[Vec<T>], cell: impl Fn(&T) -> Cell) -> Self {
+        let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        let mut frame = Frame::new(width, rows.len(), Cell::blank());
+
+        for (y, row) in rows.iter().enumerate() {
+            for (x, value) in row.iter().enumerate() {
+                frame.set(x, y, cell(value));
+            }
+        }
+
+        frame
+    }
+
+    /// Builds a frame just large enough to hold every point, which is what sparse simulations
+    /// (sand, rope knots) need since their coordinates can be anywhere.
+    pub fn from_points(points: &[((isize, isize), Cell)], background: Cell) -> Self {
+        let (Some(min_x), Some(max_x)) = (
+            points.iter().map(|((x, _), _)| *x).min(),
+            points.iter().map(|((x, _), _)| *x).max(),
+        ) else {
+            return Frame::new(0, 0, background);
+        };
+        let min_y = points.iter().map(|((_, y), _)| *y).min().unwrap_or(0);
+        let max_y = points.iter().map(|((_, y), _)| *y).max().unwrap_or(0);
+
+        let mut frame = Frame::new(
+            (max_x - min_x + 1) as usize,
+            (max_y - min_y + 1) as usize,
+            background,
+        );
+
+        for ((x, y), cell) in points {
+            frame.set((x - min_x) as usize, (y - min_y) as usize, *cell);
+        }
+
+        frame
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&Cell> {
+        if x < self.width && y < self.height {
+            self.cells.get(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, cell: Cell) {
+        if x < self.width && y < self.height {
+            self.cells[y * self.width + x] = cell;
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        self.cells
+            .chunks(self.width.max(1))
+            .map(|row| row.iter().map(|cell| cell.glyph).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn to_rgb(&self, scale: usize) -> Vec<u8> {
+        let mut pixels = Vec::with_capacity(self.width * self.height * scale * scale * 3);
+
+        for row in self.cells.chunks(self.width.max(1)) {
+            for _ in 0..scale {
+                for cell in row {
+                    for _ in 0..scale {
+                        pixels.extend([cell.color.0, cell.color.1, cell.color.2]);
+                    }
+                }
+            }
+        }
+
+        pixels
+    }
+}
+
+/// Somewhere a simulation can send its frames.
+///
+/// Sinks hold on to the first I/O error they hit and report it from `finish`, so simulations can
+/// emit frames without threading `io::Result` through their step functions.
+pub trait Visualizer {
+    fn enabled(&self) -> bool {
+        true
+    }
+
+    fn emit(&mut self, frame: Frame);
+
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The disabled visualizer. `record` never builds a frame for it, so passing `&mut ()` costs
+/// nothing.
+impl Visualizer for () {
+    fn enabled(&self) -> bool {
+        false
+    }
+
+    fn emit(&mut self, _: Frame) {}
+}
+
+pub fn record<V: Visualizer + ?Sized>(visualizer: &mut V, frame: impl FnOnce() -> Frame) {
+    if visualizer.enabled() {
+        visualizer.emit(frame());
+    }
+}
+
+fn frame_delay(fps: u32) -> Duration {
+    Duration::from_secs_f64(1.0 / fps.max(1) as f64)
+}
+
+/// Plays frames back live, clearing the screen between each one.
+pub struct Terminal<W: Write = Stdout> {
+    out: W,
+    delay: Duration,
+    error: Option<io::Error>,
+}
+
+impl Terminal<Stdout> {
+    pub fn new(fps: u32) -> Self {
+        Terminal::with_writer(io::stdout(), fps)
+    }
+}
+
+impl<W: Write> Terminal<W> {
+    pub fn with_writer(out: W, fps: u32) -> Self {
+        Terminal {
+            out,
+            delay: frame_delay(fps),
+            error: None,
+        }
+    }
+
+    fn draw(&mut self, frame: &Frame) -> io::Result<()> {
+        write!(self.out, "\x1b[2J\x1b[H")?;
+
+        for row in frame.cells.chunks(frame.width.max(1)) {
+            for cell in row {
+                let Rgb(r, g, b) = cell.color;
+                write!(self.out, "\x1b[38;2;{};{};{}m{}", r, g, b, cell.glyph)?;
+            }
+            writeln!(self.out, "\x1b[0m")?;
+        }
+
+        self.out.flush()
+    }
+}
+
+impl<W: Write> Visualizer for Terminal<W> {
+    fn emit(&mut self, frame: Frame) {
+        if self.error.is_some() {
+            return;
+        }
+
+        match self.draw(&frame) {
+            Ok(_) => thread::sleep(self.delay),
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.error.take().map_or(Ok(()), Err)
+    }
+}
+
+/// Writes each frame as `frame-00000.ppm`, `frame-00001.ppm`, ... into a directory.
+pub struct PpmDirectory {
+    directory: PathBuf,
+    scale: usize,
+    index: usize,
+    error: Option<io::Error>,
+}
+
+impl PpmDirectory {
+    pub fn new(directory: impl Into<PathBuf>, scale: usize) -> io::Result<Self> {
+        let directory = directory.into();
+        fs::create_dir_all(&directory)?;
+
+        Ok(PpmDirectory {
+            directory,
+            scale: scale.max(1),
+            index: 0,
+            error: None,
+        })
+    }
+
+    fn write(&self, frame: &Frame) -> io::Result<()> {
+        let path = self.directory.join(format!("frame-{:05}.ppm", self.index));
+        let mut out = BufWriter::new(File::create(path)?);
+
+        write!(
+            out,
+            "P6\n{} {}\n255\n",
+            frame.width * self.scale,
+            frame.height * self.scale
+        )?;
+        out.write_all(&frame.to_rgb(self.scale))?;
+        out.flush()
+    }
+}
+
+impl Visualizer for PpmDirectory {
+    fn emit(&mut self, frame: Frame) {
+        if self.error.is_some() {
+            return;
+        }
+
+        match self.write(&frame) {
+            Ok(_) => self.index += 1,
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.error.take().map_or(Ok(()), Err)
+    }
+}
+
+/// Streams frames into an animated GIF. Every frame is drawn on a canvas the size of the first
+/// one; larger frames are cropped and smaller ones padded with black.
+pub struct Gif {
+    path: PathBuf,
+    delay: u16,
+    scale: usize,
+    size: Option<(usize, usize)>,
+    encoder: Option<gif::Encoder<BufWriter<File>>>,
+    error: Option<io::Error>,
+}
+
+impl Gif {
+    pub fn new(path: impl Into<PathBuf>, fps: u32, scale: usize) -> Self {
+        Gif {
+            path: path.into(),
+            delay: (100 / fps.clamp(1, 100)) as u16,
+            scale: scale.max(1),
+            size: None,
+            encoder: None,
+            error: None,
+        }
+    }
+
+    fn write(&mut self, frame: &Frame) -> io::Result<()> {
+        let (width, height) = *self.size.get_or_insert((frame.width, frame.height));
+        let mut canvas = Frame::new(width, height, Cell::blank());
+
+        for y in 0..height {
+            for x in 0..width {
+                if let Some(cell) = frame.get(x, y) {
+                    canvas.set(x, y, *cell);
+                }
+            }
+        }
+
+        let (pixel_width, pixel_height) = (
+            dimension(width * self.scale)?,
+            dimension(height * self.scale)?,
+        );
+
+        if self.encoder.is_none() {
+            let mut encoder = gif::Encoder::new(
+                BufWriter::new(File::create(&self.path)?),
+                pixel_width,
+                pixel_height,
+                &[],
+            )
+            .map_err(io::Error::other)?;
+            encoder
+                .set_repeat(gif::Repeat::Infinite)
+                .map_err(io::Error::other)?;
+            self.encoder = Some(encoder);
+        }
+
+        let mut gif_frame =
+            gif::Frame::from_rgb(pixel_width, pixel_height, &canvas.to_rgb(self.scale));
+        gif_frame.delay = self.delay;
+
+        self.encoder
+            .as_mut()
+            .expect("encoder")
+            .write_frame(&gif_frame)
+            .map_err(io::Error::other)
+    }
+}
+
+fn dimension(value: usize) -> io::Result<u16> {
+    u16::try_from(value)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "frame too large for a GIF"))
+}
+
+impl Visualizer for Gif {
+    fn emit(&mut self, frame: Frame) {
+        if self.error.is_some() {
+            return;
+        }
+
+        if let Err(e) = self.write(&frame) {
+            self.error = Some(e);
+        }
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if let Some(encoder) = self.encoder.take() {
+            encoder.into_inner()?.flush()?;
+        }
+
+        self.error.take().map_or(Ok(()), Err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Collect(Vec<Frame>);
+
+    impl Visualizer for Collect {
+        fn emit(&mut self, frame: Frame) {
+            self.0.push(frame);
+        }
+    }
+
+    #[test]
+    fn disabled_visualizer_never_builds_frames() {
+        record(&mut (), || panic!("frame should not be built"));
+    }
+
+    #[test]
+    fn frames_from_points_fit_their_bounds() {
+        let hash = Cell::new('#', Rgb::WHITE);
+        let frame = Frame::from_points(&[((-1, 2), hash), ((1, 3), hash)], Cell::blank());
+
+        assert_eq!(frame.to_text(), "#  \n  #");
+    }
+
+    #[test]
+    fn terminal_draws_each_frame() {
+        let mut terminal = Terminal::with_writer(vec![], 1000);
+        let mut collect = Collect(vec![]);
+        let frame = Frame::from_rows(&[vec![true, false]], |&v| {
+            Cell::new(if v { '#' } else { '.' }, Rgb::WHITE)
+        });
+
+        record(&mut collect, || frame.clone());
+        record(&mut terminal, || frame.clone());
+
+        assert_eq!(collect.0, vec![frame]);
+        let output = String::from_utf8(terminal.out).unwrap();
+        assert!(output.contains("\x1b[38;2;255;255;255m#"));
+        assert!(output.contains("\x1b[38;2;255;255;255m."));
+    }
+}
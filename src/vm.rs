@@ -0,0 +1,438 @@
+use std::{
+    fmt,
+    ops::{ControlFlow, Index, IndexMut},
+};
+
+pub mod trace;
+
+/// The machine's registers, addressed by index. Puzzles give them names with constants.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Registers(Vec<isize>);
+
+impl Registers {
+    /// `count` registers, all starting at zero.
+    pub fn new(count: usize) -> Self {
+        Registers(vec![0; count])
+    }
+
+    pub fn values(&self) -> &[isize] {
+        &self.0
+    }
+}
+
+impl From<Vec<isize>> for Registers {
+    fn from(values: Vec<isize>) -> Self {
+        Registers(values)
+    }
+}
+
+impl Index<usize> for Registers {
+    type Output = isize;
+
+    fn index(&self, register: usize) -> &isize {
+        &self.0[register]
+    }
+}
+
+impl IndexMut<usize> for Registers {
+    fn index_mut(&mut self, register: usize) -> &mut isize {
+        &mut self.0[register]
+    }
+}
+
+impl fmt::Display for Registers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.0)
+    }
+}
+
+/// Where the program counter goes once an instruction has run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Effect {
+    Next,
+    /// Relative to the instruction that just ran.
+    Jump(isize),
+    Halt,
+}
+
+/// One instruction of a puzzle's assembly language.
+pub trait Instruction {
+    /// How many cycles the instruction takes. Its effect only lands at the end of the last one.
+    fn cycles(&self) -> usize {
+        1
+    }
+
+    fn execute(&self, registers: &mut Registers) -> Effect;
+}
+
+/// Why a machine stopped running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Halt {
+    /// The program counter moved to just past the last instruction.
+    Finished,
+    /// A jump landed somewhere other than the program or the spot just past it.
+    OutOfBounds(isize),
+    /// An instruction asked to stop.
+    Halted,
+    /// The instruction at `pc` was about to run a second time.
+    Loop { pc: usize },
+    /// The step limit was used up.
+    StepLimit,
+    /// A cycle hook asked to stop.
+    Interrupted,
+}
+
+/// What a cycle hook gets to see: the registers as they are *during* the cycle, before the
+/// instruction's effect has landed.
+#[derive(Debug)]
+pub struct Tick<'a, I> {
+    /// Counting from 1.
+    pub cycle: usize,
+    pub pc: usize,
+    pub instruction: &'a I,
+    pub registers: &'a Registers,
+}
+
+/// Runs a program of `I`s against a register file.
+#[derive(Debug, Clone)]
+pub struct Machine<'a, I> {
+    program: &'a [I],
+    patch: Option<(usize, &'a I)>,
+    registers: Registers,
+    pc: isize,
+    cycle: usize,
+    steps: usize,
+    executed: Vec<bool>,
+    halted: bool,
+    step_limit: Option<usize>,
+    detect_loops: bool,
+}
+
+impl<'a, I: Instruction> Machine<'a, I> {
+    /// Loop detection starts out on, with no step limit.
+    pub fn new(program: &'a [I], registers: Registers) -> Self {
+        Machine {
+            program,
+            patch: None,
+            registers,
+            pc: 0,
+            cycle: 0,
+            steps: 0,
+            executed: vec![false; program.len()],
+            halted: false,
+            step_limit: None,
+            detect_loops: true,
+        }
+    }
+
+    /// Stops with [`Halt::StepLimit`] once `limit` instructions have run.
+    pub fn with_step_limit(mut self, limit: usize) -> Self {
+        self.step_limit = Some(limit);
+        self
+    }
+
+    /// Whether to stop with [`Halt::Loop`] before running any instruction a second time. Turn
+    /// this off for programs that loop on purpose.
+    pub fn detect_loops(mut self, detect: bool) -> Self {
+        self.detect_loops = detect;
+        self
+    }
+
+    /// Runs `instruction` in place of the one at `index`, leaving the program itself alone.
+    pub fn with_patch(mut self, index: usize, instruction: &'a I) -> Self {
+        self.patch = Some((index, instruction));
+        self
+    }
+
+    pub fn program(&self) -> &'a [I] {
+        self.program
+    }
+
+    /// The instruction at `index`, taking any patch into account.
+    pub fn instruction(&self, index: usize) -> &'a I {
+        match self.patch {
+            Some((patched, instruction)) if patched == index => instruction,
+            _ => &self.program[index],
+        }
+    }
+
+    pub fn registers(&self) -> &Registers {
+        &self.registers
+    }
+
+    pub fn registers_mut(&mut self) -> &mut Registers {
+        &mut self.registers
+    }
+
+    /// The next instruction to run. Only negative once a jump has gone out of bounds.
+    pub fn pc(&self) -> isize {
+        self.pc
+    }
+
+    /// How many cycles have completed.
+    pub fn cycle(&self) -> usize {
+        self.cycle
+    }
+
+    /// How many instructions have completed.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// For every instruction, whether it has run at least once.
+    pub fn executed(&self) -> &[bool] {
+        &self.executed
+    }
+
+    pub fn run(&mut self) -> Halt {
+        self.run_with(|_| ControlFlow::Continue(()))
+    }
+
+    /// Runs until the program stops, calling `hook` on every cycle.
+    pub fn run_with(&mut self, mut hook: impl FnMut(&Tick<'_, I>) -> ControlFlow<()>) -> Halt {
+        loop {
+            if let Some(halt) = self.step_with(&mut hook) {
+                return halt;
+            }
+        }
+    }
+
+    /// Runs a single instruction, or says why it can't.
+    pub fn step(&mut self) -> Option<Halt> {
+        self.step_with(|_| ControlFlow::Continue(()))
+    }
+
+    /// Runs a single instruction, calling `hook` on each of its cycles. If the hook breaks, the
+    /// instruction is abandoned without effect or any cycles counted, so the next step
+    /// starts it over.
+    pub fn step_with(
+        &mut self,
+        mut hook: impl FnMut(&Tick<'_, I>) -> ControlFlow<()>,
+    ) -> Option<Halt> {
+        let pc = match self.check() {
+            Ok(pc) => pc,
+            Err(halt) => return Some(halt),
+        };
+        let instruction = self.instruction(pc);
+
+        for offset in 1..=instruction.cycles() {
+            let tick = Tick {
+                cycle: self.cycle + offset,
+                pc,
+                instruction,
+                registers: &self.registers,
+            };
+
+            if hook(&tick).is_break() {
+                return Some(Halt::Interrupted);
+            }
+        }
+
+        self.cycle += instruction.cycles();
+        self.steps += 1;
+        self.executed[pc] = true;
+
+        match instruction.execute(&mut self.registers) {
+            Effect::Next => self.pc += 1,
+            Effect::Jump(offset) => self.pc += offset,
+            Effect::Halt => self.halted = true,
+        }
+
+        None
+    }
+
+    /// The instruction that's allowed to run next, or why none is.
+    fn check(&self) -> Result<usize, Halt> {
+        let len = self.program.len() as isize;
+
+        match self.pc {
+            _ if self.halted => Err(Halt::Halted),
+            pc if pc == len => Err(Halt::Finished),
+            pc if !(0..len).contains(&pc) => Err(Halt::OutOfBounds(pc)),
+            _ if self.step_limit.is_some_and(|limit| self.steps >= limit) => Err(Halt::StepLimit),
+            pc if self.detect_loops && self.executed[pc as usize] => {
+                Err(Halt::Loop { pc: pc as usize })
+            }
+            pc => Ok(pc as usize),
+        }
+    }
+}
+
+/// Offers a replacement for an instruction, or `None` if it doesn't apply.
+pub type Swap<I> = fn(&I) -> Option<I>;
+
+/// A single-instruction fix that lets a program finish.
+#[derive(Debug, Clone)]
+pub struct Repair<I> {
+    pub index: usize,
+    pub replacement: I,
+    /// The registers once the patched program has finished.
+    pub registers: Registers,
+}
+
+/// Looks for one instruction to replace, using any of `swaps`, so that the program finishes
+/// instead of looping or jumping out of bounds.
+///
+/// Only instructions the unpatched program reaches are worth patching (changing any other can't
+/// alter what it does), and each attempt runs with loop detection, so it's over within one pass
+/// of the program.
+pub fn repair<I: Instruction>(
+    program: &[I],
+    registers: Registers,
+    swaps: &[Swap<I>],
+) -> Option<Repair<I>> {
+    let mut unpatched = Machine::new(program, registers.clone());
+    unpatched.run();
+
+    let candidates = unpatched
+        .executed()
+        .iter()
+        .enumerate()
+        .filter(|(_, &executed)| executed)
+        .flat_map(|(index, _)| {
+            swaps
+                .iter()
+                .filter_map(move |swap| swap(&program[index]))
+                .map(move |replacement| (index, replacement))
+        });
+
+    for (index, replacement) in candidates {
+        let mut machine = Machine::new(program, registers.clone()).with_patch(index, &replacement);
+
+        if let Halt::Finished | Halt::Halted = machine.run() {
+            let registers = machine.registers;
+
+            return Some(Repair {
+                index,
+                replacement,
+                registers,
+            });
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    enum Op {
+        Add(usize, isize),
+        Jump(isize),
+        Stop,
+        Slow,
+    }
+
+    impl Instruction for Op {
+        fn cycles(&self) -> usize {
+            match self {
+                Op::Slow => 3,
+                _ => 1,
+            }
+        }
+
+        fn execute(&self, registers: &mut Registers) -> Effect {
+            match *self {
+                Op::Add(register, value) => {
+                    registers[register] += value;
+                    Effect::Next
+                }
+                Op::Jump(offset) => Effect::Jump(offset),
+                Op::Stop => Effect::Halt,
+                Op::Slow => Effect::Next,
+            }
+        }
+    }
+
+    #[test]
+    fn halting_reasons() {
+        let looping = [Op::Add(0, 1), Op::Jump(-1)];
+        let mut machine = Machine::new(&looping, Registers::new(1));
+        assert_eq!(machine.run(), Halt::Loop { pc: 0 });
+        assert_eq!(machine.registers()[0], 1);
+
+        let mut machine = Machine::new(&looping, Registers::new(1))
+            .detect_loops(false)
+            .with_step_limit(7);
+        assert_eq!(machine.run(), Halt::StepLimit);
+        assert_eq!(machine.registers()[0], 4);
+
+        let program = [Op::Add(1, 2), Op::Jump(2), Op::Stop, Op::Add(0, 5)];
+        let mut machine = Machine::new(&program, Registers::new(2));
+        assert_eq!(machine.run(), Halt::Finished);
+        assert_eq!(machine.registers().values(), &[5, 2]);
+        assert_eq!(machine.executed(), &[true, true, false, true]);
+
+        let mut machine = Machine::new(&program[2..], Registers::new(2));
+        assert_eq!(machine.run(), Halt::Halted);
+
+        let mut machine = Machine::new(&[Op::Jump(-3)], Registers::new(0));
+        assert_eq!(machine.run(), Halt::OutOfBounds(-3));
+    }
+
+    #[test]
+    fn hooks_see_every_cycle_before_the_effect_lands() {
+        let program = [Op::Slow, Op::Add(0, 3), Op::Slow];
+        let mut machine = Machine::new(&program, Registers::new(1));
+        let mut seen = vec![];
+
+        let halt = machine.run_with(|tick| {
+            seen.push((tick.cycle, tick.pc, tick.registers[0]));
+            ControlFlow::Continue(())
+        });
+
+        assert_eq!(halt, Halt::Finished);
+        assert_eq!(
+            seen,
+            vec![
+                (1, 0, 0),
+                (2, 0, 0),
+                (3, 0, 0),
+                (4, 1, 0),
+                (5, 2, 3),
+                (6, 2, 3),
+                (7, 2, 3)
+            ]
+        );
+        assert_eq!(machine.cycle(), 7);
+    }
+
+    #[test]
+    fn interrupting_restarts_the_instruction() {
+        let program = [Op::Slow, Op::Add(0, 1)];
+        let mut machine = Machine::new(&program, Registers::new(1));
+
+        let halt = machine.run_with(|tick| match tick.cycle {
+            2 => ControlFlow::Break(()),
+            _ => ControlFlow::Continue(()),
+        });
+
+        assert_eq!(halt, Halt::Interrupted);
+        assert_eq!((machine.pc(), machine.cycle(), machine.steps()), (0, 0, 0));
+        assert_eq!(machine.run(), Halt::Finished);
+        assert_eq!((machine.cycle(), machine.steps()), (4, 2));
+    }
+
+    #[test]
+    fn repairing_a_loop() {
+        let program = [
+            Op::Add(0, 1),
+            Op::Jump(2),
+            Op::Jump(-2),
+            Op::Jump(-1),
+            Op::Add(0, 4),
+        ];
+        let swaps: [Swap<Op>; 1] = [|op| match op {
+            Op::Jump(_) => Some(Op::Jump(1)),
+            _ => None,
+        }];
+
+        let fixed = repair(&program, Registers::new(1), &swaps).unwrap();
+        assert_eq!((fixed.index, fixed.replacement), (3, Op::Jump(1)));
+        assert_eq!(fixed.registers[0], 5);
+
+        assert!(repair(&program[..4], Registers::new(1), &[]).is_none());
+    }
+}
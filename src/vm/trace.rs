@@ -0,0 +1,269 @@
+use super::{Halt, Instruction, Machine};
+use std::{
+    fmt::{self, Write as _},
+    io::{self, Stdout, Write},
+};
+
+/// Where a traced run should stop so the machine can be looked at.
+#[derive(Debug, Clone, Copy)]
+pub enum Breakpoint {
+    /// Before the instruction at this index runs.
+    Pc(usize),
+    /// After any instruction that leaves the register holding a value the test accepts.
+    Register(usize, fn(isize) -> bool),
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Breakpoint::Pc(pc) => write!(f, "pc {}", pc),
+            Breakpoint::Register(register, _) => write!(f, "register {}", register),
+        }
+    }
+}
+
+/// Runs a machine one instruction at a time, writing a line per instruction:
+///
+/// ```text
+/// cycle | pc | instruction | registers before → after
+/// ```
+pub struct Tracer<W: Write = Stdout> {
+    out: W,
+    breakpoints: Vec<Breakpoint>,
+    /// The step count the last `Pc` breakpoint stopped at, so resuming doesn't stop there again.
+    /// `Register` breakpoints are checked after the step, so resuming moves past them anyway.
+    stopped_at: Option<usize>,
+}
+
+impl Tracer<Stdout> {
+    pub fn new() -> Self {
+        Tracer::with_writer(io::stdout())
+    }
+}
+
+impl Default for Tracer<Stdout> {
+    fn default() -> Self {
+        Tracer::new()
+    }
+}
+
+impl<W: Write> Tracer<W> {
+    pub fn with_writer(out: W) -> Self {
+        Tracer {
+            out,
+            breakpoints: vec![],
+            stopped_at: None,
+        }
+    }
+
+    pub fn breakpoint(mut self, breakpoint: Breakpoint) -> Self {
+        self.breakpoints.push(breakpoint);
+        self
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+
+    /// Runs until the machine stops or a breakpoint is hit, which comes back as
+    /// [`Halt::Interrupted`]. Running again carries on from the breakpoint.
+    pub fn run<I: Instruction + fmt::Display>(
+        &mut self,
+        machine: &mut Machine<'_, I>,
+    ) -> io::Result<Halt> {
+        loop {
+            let pc = machine.pc();
+
+            if self.stopped_at != Some(machine.steps()) {
+                let hit = self
+                    .breakpoints
+                    .iter()
+                    .position(|breakpoint| match *breakpoint {
+                        Breakpoint::Pc(at) => at as isize == pc,
+                        Breakpoint::Register(..) => false,
+                    });
+
+                if let Some(idx) = hit {
+                    return self.stop(machine, idx);
+                }
+            }
+
+            let cycle = machine.cycle() + 1;
+            let before = machine.registers().clone();
+
+            if let Some(halt) = machine.step() {
+                writeln!(self.out, "halted: {:?}", halt)?;
+                return Ok(halt);
+            }
+
+            writeln!(
+                self.out,
+                "{:>6} | {:>4} | {:<16} | {} → {}",
+                cycle,
+                pc,
+                machine.instruction(pc as usize).to_string(),
+                before,
+                machine.registers()
+            )?;
+
+            let hit = self
+                .breakpoints
+                .iter()
+                .position(|breakpoint| match *breakpoint {
+                    Breakpoint::Pc(_) => false,
+                    Breakpoint::Register(register, test) => test(machine.registers()[register]),
+                });
+
+            if let Some(idx) = hit {
+                return self.stop(machine, idx);
+            }
+        }
+    }
+
+    fn stop<I: Instruction>(&mut self, machine: &Machine<'_, I>, idx: usize) -> io::Result<Halt> {
+        if let Breakpoint::Pc(_) = self.breakpoints[idx] {
+            self.stopped_at = Some(machine.steps());
+        }
+
+        writeln!(
+            self.out,
+            "stopped at breakpoint {} ({}) with pc {}",
+            idx,
+            self.breakpoints[idx],
+            machine.pc()
+        )?;
+
+        Ok(Halt::Interrupted)
+    }
+}
+
+/// The program written back out as source, one instruction per line, with a `*` against every
+/// instruction the machine has run so far.
+pub fn disassemble<I: Instruction + fmt::Display>(machine: &Machine<'_, I>) -> String {
+    let mut listing = String::new();
+
+    for (idx, executed) in machine.executed().iter().enumerate() {
+        let mark = if *executed { '*' } else { ' ' };
+        // writing to a String can't fail
+        let _ = writeln!(listing, "{:>4} {} {}", idx, mark, machine.instruction(idx));
+    }
+
+    listing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::{Effect, Registers};
+
+    enum Op {
+        Inc,
+        Jump(isize),
+        Slow,
+    }
+
+    impl Instruction for Op {
+        fn cycles(&self) -> usize {
+            match self {
+                Op::Slow => 2,
+                _ => 1,
+            }
+        }
+
+        fn execute(&self, registers: &mut Registers) -> Effect {
+            match *self {
+                Op::Inc => {
+                    registers[0] += 1;
+                    Effect::Next
+                }
+                Op::Jump(offset) => Effect::Jump(offset),
+                Op::Slow => Effect::Next,
+            }
+        }
+    }
+
+    impl fmt::Display for Op {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Op::Inc => write!(f, "inc"),
+                Op::Jump(offset) => write!(f, "jmp {:+}", offset),
+                Op::Slow => write!(f, "slow"),
+            }
+        }
+    }
+
+    fn lines(tracer: Tracer<Vec<u8>>) -> Vec<String> {
+        String::from_utf8(tracer.into_inner())
+            .unwrap()
+            .lines()
+            .map(str::trim_end)
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn tracing_every_step() {
+        let program = [Op::Slow, Op::Inc, Op::Jump(2), Op::Inc];
+        let mut machine = Machine::new(&program, Registers::new(1));
+        let mut tracer = Tracer::with_writer(vec![]);
+
+        assert_eq!(tracer.run(&mut machine).unwrap(), Halt::Finished);
+        assert_eq!(
+            lines(tracer),
+            vec![
+                "     1 |    0 | slow             | [0] → [0]",
+                "     3 |    1 | inc              | [0] → [1]",
+                "     4 |    2 | jmp +2           | [1] → [1]",
+                "halted: Finished",
+            ]
+        );
+        assert_eq!(
+            disassemble(&machine),
+            "   0 * slow\n   1 * inc\n   2 * jmp +2\n   3   inc\n"
+        );
+    }
+
+    #[test]
+    fn stopping_at_breakpoints() {
+        let program = [Op::Inc, Op::Jump(-1)];
+        let mut machine = Machine::new(&program, Registers::new(1)).detect_loops(false);
+        let mut tracer = Tracer::with_writer(vec![])
+            .breakpoint(Breakpoint::Pc(1))
+            .breakpoint(Breakpoint::Register(0, |value| value >= 3));
+
+        assert_eq!(tracer.run(&mut machine).unwrap(), Halt::Interrupted);
+        assert_eq!((machine.pc(), machine.registers()[0]), (1, 1));
+
+        assert_eq!(tracer.run(&mut machine).unwrap(), Halt::Interrupted);
+        assert_eq!((machine.pc(), machine.registers()[0]), (1, 2));
+
+        tracer.breakpoints.remove(0);
+        assert_eq!(tracer.run(&mut machine).unwrap(), Halt::Interrupted);
+        assert_eq!((machine.pc(), machine.registers()[0]), (1, 3));
+
+        let output = lines(tracer);
+        assert_eq!(output[1], "stopped at breakpoint 0 (pc 1) with pc 1");
+        assert_eq!(
+            output.last().unwrap(),
+            "stopped at breakpoint 0 (register 0) with pc 1"
+        );
+    }
+
+    #[test]
+    fn register_stops_dont_skip_the_next_pc() {
+        let program = [Op::Inc, Op::Inc];
+        let mut machine = Machine::new(&program, Registers::new(1));
+        let mut tracer = Tracer::with_writer(vec![])
+            .breakpoint(Breakpoint::Register(0, |value| value == 1))
+            .breakpoint(Breakpoint::Pc(1));
+
+        assert_eq!(tracer.run(&mut machine).unwrap(), Halt::Interrupted);
+        assert_eq!(tracer.run(&mut machine).unwrap(), Halt::Interrupted);
+        assert_eq!((machine.pc(), machine.registers()[0]), (1, 1));
+        assert_eq!(tracer.run(&mut machine).unwrap(), Halt::Finished);
+
+        let output = lines(tracer);
+        assert_eq!(output[1], "stopped at breakpoint 0 (register 0) with pc 1");
+        assert_eq!(output[2], "stopped at breakpoint 1 (pc 1) with pc 1");
+    }
+}